@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum CourtArea {
     Basket,
     // Inside the three-point line
@@ -33,6 +33,30 @@ pub enum CourtArea {
 
     // Other areas
     Center,
+
+    // The other half, each area the mirror image of the frontcourt one it is
+    // named after. Backcourt is the top of the key at the other end, where
+    // the ball is brought up from.
+    BackcourtBasket,
+    BackcourtRestrictedAreaLeft,
+    BackcourtRestrictedAreaMiddle,
+    BackcourtRestrictedAreaRight,
+    BackcourtLowPostLeft,
+    BackcourtLowPostRight,
+    BackcourtShortCornerLeft,
+    BackcourtShortCornerRight,
+    BackcourtElbowLeft,
+    BackcourtElbowRight,
+    BackcourtFreeThrowLine,
+    BackcourtMidrangeBaselineLeft,
+    BackcourtMidrangeBaselineRight,
+    BackcourtMidrangeWingLeft,
+    BackcourtMidrangeCenter,
+    BackcourtMidrangeWingRight,
+    BackcourtThreePointLineCornerLeft,
+    BackcourtThreePointLineCornerRight,
+    BackcourtThreePointLineWingLeft,
+    BackcourtThreePointLineWingRight,
     Backcourt,
 
     // Out of bounds areas
@@ -40,6 +64,10 @@ pub enum CourtArea {
     SidelineRight,
     BaselineLeft,
     BaselineRight,
+    BackcourtSidelineLeft,
+    BackcourtSidelineRight,
+    BackcourtBaselineLeft,
+    BackcourtBaselineRight,
     OutOfBounds,
 }
 
@@ -77,6 +105,26 @@ pub fn get_position_weight(area: CourtArea) -> f32 {
 
         // Furthest positions
         CourtArea::Center => 4.0,
+        CourtArea::BackcourtBasket => 5.0,
+        CourtArea::BackcourtRestrictedAreaLeft => 5.0,
+        CourtArea::BackcourtRestrictedAreaMiddle => 5.0,
+        CourtArea::BackcourtRestrictedAreaRight => 5.0,
+        CourtArea::BackcourtLowPostLeft => 5.0,
+        CourtArea::BackcourtLowPostRight => 5.0,
+        CourtArea::BackcourtShortCornerLeft => 5.0,
+        CourtArea::BackcourtShortCornerRight => 5.0,
+        CourtArea::BackcourtElbowLeft => 5.0,
+        CourtArea::BackcourtElbowRight => 5.0,
+        CourtArea::BackcourtFreeThrowLine => 5.0,
+        CourtArea::BackcourtMidrangeBaselineLeft => 5.0,
+        CourtArea::BackcourtMidrangeBaselineRight => 5.0,
+        CourtArea::BackcourtMidrangeWingLeft => 5.0,
+        CourtArea::BackcourtMidrangeCenter => 5.0,
+        CourtArea::BackcourtMidrangeWingRight => 5.0,
+        CourtArea::BackcourtThreePointLineCornerLeft => 5.0,
+        CourtArea::BackcourtThreePointLineCornerRight => 5.0,
+        CourtArea::BackcourtThreePointLineWingLeft => 5.0,
+        CourtArea::BackcourtThreePointLineWingRight => 5.0,
         CourtArea::Backcourt => 5.0,
 
         // Out of bounds and boundaries
@@ -84,10 +132,17 @@ pub fn get_position_weight(area: CourtArea) -> f32 {
         CourtArea::SidelineRight => f32::INFINITY,
         CourtArea::BaselineLeft => f32::INFINITY,
         CourtArea::BaselineRight => f32::INFINITY,
+        CourtArea::BackcourtSidelineLeft => f32::INFINITY,
+        CourtArea::BackcourtSidelineRight => f32::INFINITY,
+        CourtArea::BackcourtBaselineLeft => f32::INFINITY,
+        CourtArea::BackcourtBaselineRight => f32::INFINITY,
         CourtArea::OutOfBounds => f32::INFINITY,
     }
 }
 
+// Length of the half court from the basket to the half court line, in feet
+const HALF_COURT: f32 = 41.75;
+
 struct Node {
     area: CourtArea,
     f_score: f32,
@@ -114,7 +169,17 @@ impl PartialOrd for Node {
     }
 }
 
-// Modified A* implementation that uses weights
+// Feet between the spots of two areas
+pub fn distance(from: CourtArea, to: CourtArea) -> f32 {
+    let (x1, y1) = from.position();
+    let (x2, y2) = to.position();
+    ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()
+}
+
+// A* over the court graph with moves weighted by the distance between areas.
+// The straight line to the goal is never longer than a path along the graph,
+// so the heuristic is admissible and the path found is the shortest. Players
+// only step out of bounds when that is where they are headed.
 pub fn find_path(start: CourtArea, goal: CourtArea) -> Option<Vec<CourtArea>> {
     // Handle same location case
     if start == goal {
@@ -130,7 +195,7 @@ pub fn find_path(start: CourtArea, goal: CourtArea) -> Option<Vec<CourtArea>> {
     g_scores.insert(start, 0.0);
     open_set.push(Node {
         area: start,
-        f_score: get_position_weight(start),
+        f_score: distance(start, goal),
         g_score: 0.0,
     });
 
@@ -156,13 +221,12 @@ pub fn find_path(start: CourtArea, goal: CourtArea) -> Option<Vec<CourtArea>> {
         closed_set.insert(current.area);
 
         // Get neighboring areas
-        for &next in can_move_to(current.area).iter() {
-            if closed_set.contains(&next) {
+        for next in sorted_moves(current.area) {
+            if closed_set.contains(&next) || (next.is_out_of_bounds() && next != goal) {
                 continue;
             }
 
-            // Cost to move is the weight of the next position
-            let tentative_g_score = current.g_score + get_position_weight(next);
+            let tentative_g_score = current.g_score + distance(current.area, next);
 
             if tentative_g_score < *g_scores.get(&next).unwrap_or(&f32::INFINITY) {
                 // This path is better than any previous one
@@ -170,8 +234,7 @@ pub fn find_path(start: CourtArea, goal: CourtArea) -> Option<Vec<CourtArea>> {
                 g_scores.insert(next, tentative_g_score);
 
                 // f_score is current cost plus estimated cost to goal
-                let h_score = get_position_weight(next); // heuristic
-                let f_score = tentative_g_score + h_score;
+                let f_score = tentative_g_score + distance(next, goal);
 
                 open_set.push(Node {
                     area: next,
@@ -189,14 +252,22 @@ pub fn find_path(start: CourtArea, goal: CourtArea) -> Option<Vec<CourtArea>> {
 fn test_long_paths() {
     // Let's verify each path is actually possible by checking the graph connections
     let cases = vec![
-        (CourtArea::Backcourt, CourtArea::RestrictedAreaMiddle, 4),
-        // Path from left three point corner to right three point corner:
-        // ThreePointLineCornerLeft -> ThreePointLineWingLeft ->
-        // ThreePointLineCenter -> ThreePointLineWingRight -> ThreePointLineCornerRight
+        // Backcourt -> Center -> ThreePointLineCenter -> MidrangeCenter ->
+        // FreeThrowLine -> RestrictedAreaMiddle
+        (CourtArea::Backcourt, CourtArea::RestrictedAreaMiddle, 5),
+        // Corner to corner is shorter along the baseline than around the arc:
+        // ThreePointLineCornerLeft -> MidrangeBaselineLeft -> LowPostLeft ->
+        // RestrictedAreaMiddle -> LowPostRight -> MidrangeBaselineRight ->
+        // ThreePointLineCornerRight
         (
             CourtArea::ThreePointLineCornerLeft,
             CourtArea::ThreePointLineCornerRight,
-            4,
+            6,
+        ),
+        (
+            CourtArea::ThreePointLineWingLeft,
+            CourtArea::ThreePointLineWingRight,
+            2,
         ),
     ];
 
@@ -211,10 +282,10 @@ fn test_long_paths() {
 
         let path = result.unwrap();
         assert_eq!(
-            path.len(),
+            path.len() - 1,
             expected_moves,
-            "Path length {} from {:?} to {:?} not equal to expected {}",
-            path.len(),
+            "Moves {} from {:?} to {:?} not equal to expected {}",
+            path.len() - 1,
             start,
             goal,
             expected_moves
@@ -224,31 +295,195 @@ fn test_long_paths() {
     }
 }
 
+#[test]
+fn test_every_area_is_reachable() {
+    let inbounds = CourtArea::ALL.into_iter().filter(|a| !a.is_out_of_bounds());
+    for start in inbounds.clone() {
+        for goal in inbounds.clone() {
+            let path = find_path(start, goal)
+                .unwrap_or_else(|| panic!("No path from {:?} to {:?}", start, goal));
+            assert_eq!(path[0], start);
+            assert_eq!(*path.last().unwrap(), goal);
+            let mut length = 0.0;
+            for step in path.windows(2) {
+                assert!(can_move_to(step[0]).contains(&step[1]));
+                assert!(!step[1].is_out_of_bounds());
+                length += distance(step[0], step[1]);
+            }
+            // The heuristic never overestimates
+            assert!(distance(start, goal) <= length + f32::EPSILON);
+        }
+    }
+}
+
+#[test]
+fn test_adjacency_is_symmetric() {
+    for area in CourtArea::ALL {
+        for next in can_move_to(area) {
+            assert!(
+                can_move_to(next).contains(&area),
+                "{:?} moves to {:?} but not back",
+                area,
+                next
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mirror_is_exact() {
+    for area in CourtArea::ALL {
+        let mirrored = area.mirror();
+        assert_eq!(mirrored.mirror(), area);
+        if area.is_out_of_bounds() {
+            assert!(mirrored.is_out_of_bounds());
+        } else if area != CourtArea::Center {
+            assert_ne!(area.is_front_court(), mirrored.is_front_court());
+        }
+        let (x, y) = area.position();
+        if area != CourtArea::OutOfBounds {
+            assert_eq!(
+                mirrored.position(),
+                (-x, 2.0 * HALF_COURT - y),
+                "{:?}",
+                area
+            );
+        }
+    }
+}
+
 impl CourtArea {
+    pub const ALL: [CourtArea; 52] = [
+        CourtArea::Basket,
+        CourtArea::RestrictedAreaLeft,
+        CourtArea::RestrictedAreaMiddle,
+        CourtArea::RestrictedAreaRight,
+        CourtArea::LowPostLeft,
+        CourtArea::LowPostRight,
+        CourtArea::ShortCornerLeft,
+        CourtArea::ShortCornerRight,
+        CourtArea::ElbowLeft,
+        CourtArea::ElbowRight,
+        CourtArea::FreeThrowLine,
+        CourtArea::MidrangeBaselineLeft,
+        CourtArea::MidrangeBaselineRight,
+        CourtArea::MidrangeWingLeft,
+        CourtArea::MidrangeCenter,
+        CourtArea::MidrangeWingRight,
+        CourtArea::ThreePointLineCornerLeft,
+        CourtArea::ThreePointLineCornerRight,
+        CourtArea::ThreePointLineWingLeft,
+        CourtArea::ThreePointLineWingRight,
+        CourtArea::ThreePointLineCenter,
+        CourtArea::Center,
+        CourtArea::BackcourtBasket,
+        CourtArea::BackcourtRestrictedAreaLeft,
+        CourtArea::BackcourtRestrictedAreaMiddle,
+        CourtArea::BackcourtRestrictedAreaRight,
+        CourtArea::BackcourtLowPostLeft,
+        CourtArea::BackcourtLowPostRight,
+        CourtArea::BackcourtShortCornerLeft,
+        CourtArea::BackcourtShortCornerRight,
+        CourtArea::BackcourtElbowLeft,
+        CourtArea::BackcourtElbowRight,
+        CourtArea::BackcourtFreeThrowLine,
+        CourtArea::BackcourtMidrangeBaselineLeft,
+        CourtArea::BackcourtMidrangeBaselineRight,
+        CourtArea::BackcourtMidrangeWingLeft,
+        CourtArea::BackcourtMidrangeCenter,
+        CourtArea::BackcourtMidrangeWingRight,
+        CourtArea::BackcourtThreePointLineCornerLeft,
+        CourtArea::BackcourtThreePointLineCornerRight,
+        CourtArea::BackcourtThreePointLineWingLeft,
+        CourtArea::BackcourtThreePointLineWingRight,
+        CourtArea::Backcourt,
+        CourtArea::SidelineLeft,
+        CourtArea::SidelineRight,
+        CourtArea::BaselineLeft,
+        CourtArea::BaselineRight,
+        CourtArea::BackcourtSidelineLeft,
+        CourtArea::BackcourtSidelineRight,
+        CourtArea::BackcourtBaselineLeft,
+        CourtArea::BackcourtBaselineRight,
+        CourtArea::OutOfBounds,
+    ];
+
+    // Spot of the area in feet, from the basket towards half court with
+    // negative x on the left. The backcourt continues past half court
+    // towards the other basket.
+    pub fn position(&self) -> (f32, f32) {
+        match self {
+            CourtArea::Basket => (0.0, 0.0),
+            CourtArea::RestrictedAreaLeft => (-3.0, 1.0),
+            CourtArea::RestrictedAreaMiddle => (0.0, 3.0),
+            CourtArea::RestrictedAreaRight => (3.0, 1.0),
+            CourtArea::LowPostLeft => (-7.0, 2.0),
+            CourtArea::LowPostRight => (7.0, 2.0),
+            CourtArea::ShortCornerLeft => (-12.0, -2.0),
+            CourtArea::ShortCornerRight => (12.0, -2.0),
+            CourtArea::ElbowLeft => (-8.0, 14.0),
+            CourtArea::ElbowRight => (8.0, 14.0),
+            CourtArea::FreeThrowLine => (0.0, 14.0),
+            CourtArea::MidrangeBaselineLeft => (-17.0, 0.0),
+            CourtArea::MidrangeBaselineRight => (17.0, 0.0),
+            CourtArea::MidrangeWingLeft => (-14.0, 11.0),
+            CourtArea::MidrangeCenter => (0.0, 19.0),
+            CourtArea::MidrangeWingRight => (14.0, 11.0),
+            CourtArea::ThreePointLineCornerLeft => (-22.0, -1.0),
+            CourtArea::ThreePointLineCornerRight => (22.0, -1.0),
+            CourtArea::ThreePointLineWingLeft => (-17.0, 18.0),
+            CourtArea::ThreePointLineWingRight => (17.0, 18.0),
+            CourtArea::ThreePointLineCenter => (0.0, 25.0),
+            CourtArea::Center => (0.0, HALF_COURT),
+            CourtArea::SidelineLeft => (-25.0, 10.0),
+            CourtArea::SidelineRight => (25.0, 10.0),
+            CourtArea::BaselineLeft => (-10.0, -5.25),
+            CourtArea::BaselineRight => (10.0, -5.25),
+            CourtArea::OutOfBounds => (0.0, -10.0),
+            // The frontcourt spot turned around center court
+            area => {
+                let (x, y) = area.mirror().position();
+                (-x, 2.0 * HALF_COURT - y)
+            }
+        }
+    }
+
+    pub fn is_out_of_bounds(&self) -> bool {
+        matches!(
+            self,
+            CourtArea::SidelineLeft
+                | CourtArea::SidelineRight
+                | CourtArea::BaselineLeft
+                | CourtArea::BaselineRight
+                | CourtArea::BackcourtSidelineLeft
+                | CourtArea::BackcourtSidelineRight
+                | CourtArea::BackcourtBaselineLeft
+                | CourtArea::BackcourtBaselineRight
+                | CourtArea::OutOfBounds
+        )
+    }
+
+    // Make chance of an average shooter from this area
     pub fn shot_chance(&self) -> f32 {
         match self {
             CourtArea::Basket
             | CourtArea::RestrictedAreaLeft
             | CourtArea::RestrictedAreaRight
-            | CourtArea::RestrictedAreaMiddle => 1.0,
-            CourtArea::LowPostRight | CourtArea::LowPostLeft => 0.9,
-            CourtArea::ShortCornerLeft | CourtArea::ShortCornerRight => 0.85,
-            CourtArea::ElbowLeft | CourtArea::ElbowRight | CourtArea::FreeThrowLine => 0.8,
-            CourtArea::Center => 0.1,
-            CourtArea::ThreePointLineCornerLeft | CourtArea::ThreePointLineCornerRight => 1.0,
+            | CourtArea::RestrictedAreaMiddle => 0.6,
+            CourtArea::LowPostRight | CourtArea::LowPostLeft => 0.5,
+            CourtArea::ShortCornerLeft | CourtArea::ShortCornerRight => 0.45,
+            CourtArea::ElbowLeft | CourtArea::ElbowRight | CourtArea::FreeThrowLine => 0.42,
+            CourtArea::Center => 0.03,
+            CourtArea::ThreePointLineCornerLeft | CourtArea::ThreePointLineCornerRight => 0.38,
             CourtArea::ThreePointLineWingLeft
             | CourtArea::ThreePointLineWingRight
-            | CourtArea::ThreePointLineCenter => 1.0,
+            | CourtArea::ThreePointLineCenter => 0.35,
             CourtArea::MidrangeCenter
             | CourtArea::MidrangeWingLeft
-            | CourtArea::MidrangeWingRight => 0.7,
-            CourtArea::MidrangeBaselineLeft | CourtArea::MidrangeBaselineRight => 0.7,
-            CourtArea::Backcourt => 0.0,
-            CourtArea::SidelineLeft
-            | CourtArea::SidelineRight
-            | CourtArea::BaselineLeft
-            | CourtArea::BaselineRight
-            | CourtArea::OutOfBounds => 0.0,
+            | CourtArea::MidrangeWingRight => 0.4,
+            CourtArea::MidrangeBaselineLeft | CourtArea::MidrangeBaselineRight => 0.4,
+            // The other half and out of bounds
+            _ => 0.0,
         }
     }
     pub fn points(&self) -> u8 {
@@ -275,27 +510,82 @@ impl CourtArea {
             CourtArea::ThreePointLineWingRight,
             CourtArea::ThreePointLineWingLeft,
             CourtArea::ThreePointLineCenter,
-            CourtArea::Backcourt,
             CourtArea::Center,
         ];
+        // Anything from the other half is a heave from way downtown
+        let backcourt = !self.is_front_court() && !self.is_out_of_bounds();
         if two_points.contains(self) {
             return 2;
-        } else if three_points.contains(self) {
+        } else if three_points.contains(self) || backcourt {
             return 3;
         } else {
             return 0;
         }
     }
+    // Past half court on the side of the basket being attacked. The half
+    // court line itself belongs to the backcourt.
     pub fn is_front_court(&self) -> bool {
+        !self.is_out_of_bounds() && self.position().1 < HALF_COURT
+    }
+
+    // The same spot seen from the other basket. Areas are relative to the
+    // basket the offense attacks, so everyone is mirrored when the ball
+    // changes hands. Center court and the generic out of bounds area are the
+    // same from both ends.
+    pub fn mirror(&self) -> CourtArea {
         match self {
-            CourtArea::Backcourt
-            | CourtArea::OutOfBounds
-            | CourtArea::SidelineLeft
-            | CourtArea::SidelineRight
-            | CourtArea::ThreePointLineCenter
-            | CourtArea::ThreePointLineWingLeft
-            | CourtArea::ThreePointLineWingRight => false,
-            _ => true,
+            CourtArea::Basket => CourtArea::BackcourtBasket,
+            CourtArea::RestrictedAreaLeft => CourtArea::BackcourtRestrictedAreaLeft,
+            CourtArea::RestrictedAreaMiddle => CourtArea::BackcourtRestrictedAreaMiddle,
+            CourtArea::RestrictedAreaRight => CourtArea::BackcourtRestrictedAreaRight,
+            CourtArea::LowPostLeft => CourtArea::BackcourtLowPostLeft,
+            CourtArea::LowPostRight => CourtArea::BackcourtLowPostRight,
+            CourtArea::ShortCornerLeft => CourtArea::BackcourtShortCornerLeft,
+            CourtArea::ShortCornerRight => CourtArea::BackcourtShortCornerRight,
+            CourtArea::ElbowLeft => CourtArea::BackcourtElbowLeft,
+            CourtArea::ElbowRight => CourtArea::BackcourtElbowRight,
+            CourtArea::FreeThrowLine => CourtArea::BackcourtFreeThrowLine,
+            CourtArea::MidrangeBaselineLeft => CourtArea::BackcourtMidrangeBaselineLeft,
+            CourtArea::MidrangeBaselineRight => CourtArea::BackcourtMidrangeBaselineRight,
+            CourtArea::MidrangeWingLeft => CourtArea::BackcourtMidrangeWingLeft,
+            CourtArea::MidrangeCenter => CourtArea::BackcourtMidrangeCenter,
+            CourtArea::MidrangeWingRight => CourtArea::BackcourtMidrangeWingRight,
+            CourtArea::ThreePointLineCornerLeft => CourtArea::BackcourtThreePointLineCornerLeft,
+            CourtArea::ThreePointLineCornerRight => CourtArea::BackcourtThreePointLineCornerRight,
+            CourtArea::ThreePointLineWingLeft => CourtArea::BackcourtThreePointLineWingLeft,
+            CourtArea::ThreePointLineWingRight => CourtArea::BackcourtThreePointLineWingRight,
+            CourtArea::ThreePointLineCenter => CourtArea::Backcourt,
+            CourtArea::SidelineLeft => CourtArea::BackcourtSidelineLeft,
+            CourtArea::SidelineRight => CourtArea::BackcourtSidelineRight,
+            CourtArea::BaselineLeft => CourtArea::BackcourtBaselineLeft,
+            CourtArea::BaselineRight => CourtArea::BackcourtBaselineRight,
+            CourtArea::BackcourtBasket => CourtArea::Basket,
+            CourtArea::BackcourtRestrictedAreaLeft => CourtArea::RestrictedAreaLeft,
+            CourtArea::BackcourtRestrictedAreaMiddle => CourtArea::RestrictedAreaMiddle,
+            CourtArea::BackcourtRestrictedAreaRight => CourtArea::RestrictedAreaRight,
+            CourtArea::BackcourtLowPostLeft => CourtArea::LowPostLeft,
+            CourtArea::BackcourtLowPostRight => CourtArea::LowPostRight,
+            CourtArea::BackcourtShortCornerLeft => CourtArea::ShortCornerLeft,
+            CourtArea::BackcourtShortCornerRight => CourtArea::ShortCornerRight,
+            CourtArea::BackcourtElbowLeft => CourtArea::ElbowLeft,
+            CourtArea::BackcourtElbowRight => CourtArea::ElbowRight,
+            CourtArea::BackcourtFreeThrowLine => CourtArea::FreeThrowLine,
+            CourtArea::BackcourtMidrangeBaselineLeft => CourtArea::MidrangeBaselineLeft,
+            CourtArea::BackcourtMidrangeBaselineRight => CourtArea::MidrangeBaselineRight,
+            CourtArea::BackcourtMidrangeWingLeft => CourtArea::MidrangeWingLeft,
+            CourtArea::BackcourtMidrangeCenter => CourtArea::MidrangeCenter,
+            CourtArea::BackcourtMidrangeWingRight => CourtArea::MidrangeWingRight,
+            CourtArea::BackcourtThreePointLineCornerLeft => CourtArea::ThreePointLineCornerLeft,
+            CourtArea::BackcourtThreePointLineCornerRight => CourtArea::ThreePointLineCornerRight,
+            CourtArea::BackcourtThreePointLineWingLeft => CourtArea::ThreePointLineWingLeft,
+            CourtArea::BackcourtThreePointLineWingRight => CourtArea::ThreePointLineWingRight,
+            CourtArea::Backcourt => CourtArea::ThreePointLineCenter,
+            CourtArea::BackcourtSidelineLeft => CourtArea::SidelineLeft,
+            CourtArea::BackcourtSidelineRight => CourtArea::SidelineRight,
+            CourtArea::BackcourtBaselineLeft => CourtArea::BaselineLeft,
+            CourtArea::BackcourtBaselineRight => CourtArea::BaselineRight,
+            CourtArea::Center => CourtArea::Center,
+            CourtArea::OutOfBounds => CourtArea::OutOfBounds,
         }
     }
 }
@@ -316,6 +606,7 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowLeft,
             CourtArea::FreeThrowLine,
             CourtArea::Basket,
+            CourtArea::BaselineLeft,
         ]
         .into(),
         CourtArea::RestrictedAreaMiddle => [
@@ -328,6 +619,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowRight,
             CourtArea::FreeThrowLine,
             CourtArea::Basket,
+            CourtArea::BaselineLeft,
+            CourtArea::BaselineRight,
         ]
         .into(),
         CourtArea::RestrictedAreaRight => [
@@ -338,6 +631,7 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowRight,
             CourtArea::FreeThrowLine,
             CourtArea::Basket,
+            CourtArea::BaselineRight,
         ]
         .into(),
         CourtArea::LowPostLeft => [
@@ -348,6 +642,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowLeft,
             CourtArea::MidrangeBaselineLeft,
             CourtArea::MidrangeWingLeft,
+            CourtArea::FreeThrowLine,
+            CourtArea::BaselineLeft,
         ]
         .into(),
         CourtArea::LowPostRight => [
@@ -358,6 +654,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowRight,
             CourtArea::MidrangeBaselineRight,
             CourtArea::MidrangeWingRight,
+            CourtArea::FreeThrowLine,
+            CourtArea::BaselineRight,
         ]
         .into(),
         CourtArea::ShortCornerLeft => [
@@ -365,6 +663,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ShortCornerLeft,
             CourtArea::ElbowLeft,
             CourtArea::MidrangeBaselineLeft,
+            CourtArea::LowPostLeft,
+            CourtArea::BaselineLeft,
         ]
         .into(),
         CourtArea::ShortCornerRight => [
@@ -372,6 +672,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ShortCornerRight,
             CourtArea::ElbowRight,
             CourtArea::MidrangeBaselineRight,
+            CourtArea::LowPostRight,
+            CourtArea::BaselineRight,
         ]
         .into(),
         CourtArea::ElbowLeft => [
@@ -382,6 +684,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::MidrangeWingLeft,
             CourtArea::MidrangeBaselineLeft,
             CourtArea::MidrangeCenter,
+            CourtArea::LowPostLeft,
+            CourtArea::ShortCornerLeft,
         ]
         .into(),
         CourtArea::ElbowRight => [
@@ -392,6 +696,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::MidrangeWingRight,
             CourtArea::MidrangeBaselineRight,
             CourtArea::MidrangeCenter,
+            CourtArea::LowPostRight,
+            CourtArea::ShortCornerRight,
         ]
         .into(),
         CourtArea::FreeThrowLine => [
@@ -415,6 +721,9 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowLeft,
             CourtArea::MidrangeWingLeft,
             CourtArea::ThreePointLineCornerLeft,
+            CourtArea::ThreePointLineWingLeft,
+            CourtArea::SidelineLeft,
+            CourtArea::BaselineLeft,
         ]
         .into(),
         CourtArea::MidrangeBaselineRight => [
@@ -424,6 +733,9 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ElbowRight,
             CourtArea::MidrangeWingRight,
             CourtArea::ThreePointLineCornerRight,
+            CourtArea::ThreePointLineWingRight,
+            CourtArea::SidelineRight,
+            CourtArea::BaselineRight,
         ]
         .into(),
         CourtArea::MidrangeWingLeft => [
@@ -435,6 +747,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::LowPostLeft,
             CourtArea::ThreePointLineWingLeft,
             CourtArea::ThreePointLineCenter,
+            CourtArea::ThreePointLineCornerLeft,
+            CourtArea::SidelineLeft,
         ]
         .into(),
         CourtArea::MidrangeWingRight => [
@@ -446,6 +760,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::LowPostRight,
             CourtArea::ThreePointLineWingRight,
             CourtArea::ThreePointLineCenter,
+            CourtArea::ThreePointLineCornerRight,
+            CourtArea::SidelineRight,
         ]
         .into(),
         CourtArea::MidrangeCenter => [
@@ -465,6 +781,7 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ThreePointLineWingLeft,
             CourtArea::MidrangeBaselineLeft,
             CourtArea::MidrangeWingLeft,
+            CourtArea::SidelineLeft,
         ]
         .into(),
         CourtArea::ThreePointLineCornerRight => [
@@ -472,6 +789,7 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ThreePointLineWingRight,
             CourtArea::MidrangeBaselineRight,
             CourtArea::MidrangeWingRight,
+            CourtArea::SidelineRight,
         ]
         .into(),
         CourtArea::ThreePointLineWingLeft => [
@@ -481,6 +799,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::MidrangeCenter,
             CourtArea::MidrangeBaselineLeft,
             CourtArea::ThreePointLineCornerLeft,
+            CourtArea::Center,
+            CourtArea::SidelineLeft,
         ]
         .into(),
         CourtArea::ThreePointLineWingRight => [
@@ -490,6 +810,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::MidrangeCenter,
             CourtArea::MidrangeBaselineRight,
             CourtArea::ThreePointLineCornerRight,
+            CourtArea::Center,
+            CourtArea::SidelineRight,
         ]
         .into(),
         CourtArea::ThreePointLineCenter => [
@@ -508,13 +830,8 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
             CourtArea::ThreePointLineWingLeft,
             CourtArea::ThreePointLineWingRight,
             CourtArea::Backcourt,
-        ]
-        .into(),
-        CourtArea::Backcourt => [
-            CourtArea::Backcourt,
-            CourtArea::Center,
-            CourtArea::ThreePointLineWingLeft,
-            CourtArea::ThreePointLineWingRight,
+            CourtArea::BackcourtThreePointLineWingLeft,
+            CourtArea::BackcourtThreePointLineWingRight,
         ]
         .into(),
         CourtArea::SidelineLeft => [
@@ -552,9 +869,28 @@ pub fn can_move_to(current_area: CourtArea) -> HashSet<CourtArea> {
         ]
         .into(),
         CourtArea::OutOfBounds => [CourtArea::OutOfBounds].into(),
+        // The backcourt connects like the frontcourt it mirrors
+        area => can_move_to(area.mirror())
+            .into_iter()
+            .map(|next| next.mirror())
+            .collect(),
     }
 }
 
+// Same as can_move_to but in a stable order, so seeded games pick the same areas
+pub fn sorted_moves(current_area: CourtArea) -> Vec<CourtArea> {
+    let mut areas: Vec<CourtArea> = can_move_to(current_area).into_iter().collect();
+    areas.sort();
+    areas
+}
+
+// Areas a player can wander to without stepping out of bounds
+pub fn inbounds_moves(current_area: CourtArea) -> Vec<CourtArea> {
+    let mut areas = sorted_moves(current_area);
+    areas.retain(|area| !area.is_out_of_bounds() || *area == current_area);
+    areas
+}
+
 pub fn go_towards(area: CourtArea, target: CourtArea) -> CourtArea {
     if let Some(path) = find_path(area, target) {
         if path.len() == 1 {
@@ -0,0 +1,202 @@
+use crate::game::court::{find_path, CourtArea};
+use crate::game::event::{defense, pass};
+use crate::game::{Game, Possession};
+use crate::player::player_state::PlayerAction;
+use rand::Rng;
+
+// Passing or driving only pays off if the next look is clearly better, the
+// ball has to move again and the defense gets to recover. Drives are
+// discounted for every step to the spot of the shot.
+const PASS_DISCOUNT: f32 = 0.85;
+const DRIVE_DISCOUNT: f32 = 0.85;
+// Seconds left on the clock below which passing and driving lose their value
+const LATE_CLOCK: f32 = 4.0;
+// Points a possession is worth with a full shot clock. Early on, moving the
+// ball and running the offense beats settling for a worse shot.
+const POSSESSION_VALUE: f32 = 1.3;
+// Dribbling in place lets the defense get set, a swing pass doesn't
+const HOLD_FACTOR: f32 = 0.9;
+// How far off a 0 intelligence player can be judging an option, as a share
+// of its value
+const MAX_MISJUDGEMENT: f32 = 0.5;
+
+// Share of an option's value a player with `intelligence` can be off by
+pub fn misjudgement(intelligence: i32) -> f32 {
+    (100 - intelligence).clamp(0, 100) as f32 / 100.0 * MAX_MISJUDGEMENT
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    Shoot,
+    Pass,
+    // Keep the ball and let the play develop
+    Hold,
+    // Drive one step towards the rim
+    Drive(CourtArea),
+}
+
+impl Game {
+    // What points are worth right now. On the last possession of a close game
+    // a shot that can't tie or win is worth much less.
+    fn points_worth(&self, team: Possession, points: u8) -> f32 {
+        let (own, other) = match team {
+            Possession::Home => (self.state.score.0, self.state.score.1),
+            Possession::Away => (self.state.score.1, self.state.score.0),
+        };
+        let deficit = other as i32 - own as i32;
+        let last_possession = self.state.period >= self.rules.periods
+            && self.state.time.as_secs() <= self.rules.shot_clock;
+        if last_possession && deficit > 0 && (points as i32) < deficit {
+            points as f32 * 0.5
+        } else {
+            points as f32
+        }
+    }
+
+    // Expected points if the player at `index` of `team` shot from `area`
+    // with their defender where they are now
+    pub fn shot_value(&self, team: Possession, index: usize, area: CourtArea) -> f32 {
+        let (player, state) = &self.state.team_state[team.index()].active_players[index];
+        let mut shooter = *state;
+        shooter.current_area = area;
+        let points = area.points();
+        let mut chance = shooter.calculate_shot_chance(player.attributes());
+        if points == 3 {
+            chance *= self.rules.three_point_factor();
+        }
+        chance *= self.scheme_shot_factor(team, area);
+        let (defender, defender_state) = &self.state.team_state[team.other().index()]
+            .active_players[self.defender_of(team, index)];
+        chance *= defense::contest_factor(defender_state, defender.attributes(), &shooter);
+        chance * self.points_worth(team, points)
+    }
+
+    // Best look a pass can create, or keeping the possession going with it
+    // if it is worth `keep` and the pass is safe
+    fn pass_value(&self, team: Possession, index: usize, keep: f32) -> f32 {
+        (0..5)
+            .filter(|&i| i != index)
+            .map(|i| {
+                let look = pass::target_value(self, team, index, i) * PASS_DISCOUNT;
+                let swing = keep * (1.0 - pass::turnover_chance(self, team, index, i));
+                look.max(swing)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    // Next step of the drive to the rim and the expected points of the best
+    // shot along the way, None when there's nowhere left to drive
+    fn drive_value(&self, team: Possession, index: usize) -> Option<(CourtArea, f32)> {
+        let (player, state) = &self.state.team_state[team.index()].active_players[index];
+        let path = find_path(state.current_area, CourtArea::RestrictedAreaMiddle)?;
+        let next = *path.get(1)?;
+        let (defender, defender_state) = &self.state.team_state[team.other().index()]
+            .active_players[self.defender_of(team, index)];
+        let a = player.attributes();
+        let d = defender.attributes();
+        let steal = defense::steal_chance(defender_state, d, a.handle);
+        // Beating the defender off the dribble
+        let blow_by = 1.0 + (a.handle + a.spd - d.perimeter_def - d.spd) as f32 / 400.0;
+        let mut discount = 1.0;
+        let best = path[1..]
+            .iter()
+            .map(|&area| {
+                discount *= DRIVE_DISCOUNT;
+                self.shot_value(team, index, area) * discount
+            })
+            .fold(0.0, f32::max);
+        Some((next, best * blow_by * (1.0 - steal)))
+    }
+
+    // Expected points of each option the ball handler has
+    pub fn options(&self) -> Vec<(Choice, f32)> {
+        let Some((team, index)) = self.state.possession else {
+            return Vec::new();
+        };
+        let area = self.state.team_state[team.index()].active_players[index]
+            .1
+            .current_area;
+        // With the clock running out only a shot is worth anything
+        let seconds = self.state.shot_clock.min(self.state.time).as_secs_f32();
+        let time_left = (seconds / LATE_CLOCK).min(1.0);
+        let patience =
+            (self.state.shot_clock.as_secs_f32() / self.rules.shot_clock as f32).min(1.0);
+        let keep = POSSESSION_VALUE * patience;
+        // On a fast break there's no waiting for the offense to set up
+        let hold = if self.is_fast_break() {
+            0.0
+        } else {
+            keep * HOLD_FACTOR
+        };
+        let mut options = vec![
+            (Choice::Shoot, self.shot_value(team, index, area)),
+            (Choice::Pass, self.pass_value(team, index, keep) * time_left),
+            (Choice::Hold, hold * time_left),
+        ];
+        if let Some((next, value)) = self.drive_value(team, index) {
+            options.push((Choice::Drive(next), value * time_left));
+        }
+        options
+    }
+
+    // The ball handler shoots, passes or drives, whichever they judge to be
+    // worth the most. Smarter players misjudge the options less.
+    pub fn decide(&mut self) {
+        let Some((team, index)) = self.state.possession else {
+            return;
+        };
+        let intelligence = self.state.team_state[team.index()].active_players[index]
+            .0
+            .attributes()
+            .intelligence;
+        let misjudgement = misjudgement(intelligence);
+        let choice = self
+            .options()
+            .into_iter()
+            .map(|(choice, value)| {
+                let judged = value * (1.0 + self.rng.gen_range(-1.0..=1.0) * misjudgement);
+                (choice, judged)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(Choice::Shoot, |(choice, _)| choice);
+
+        let (player, state) = &mut self.state.team_state[team.index()].active_players[index];
+        match choice {
+            Choice::Shoot => state.action = state.shot_action(player.attributes(), &mut self.rng),
+            Choice::Pass => state.action = PlayerAction::Pass,
+            Choice::Hold => state.action = PlayerAction::Idle,
+            Choice::Drive(next) => {
+                state.action = PlayerAction::Drive;
+                state.current_area = next;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_open_look_beats_late_pass() {
+    use std::time::Duration;
+
+    let mut game = crate::game::test_game(3);
+    game.state.possession = Some((Possession::Home, 4));
+    for (_, state) in game.state.team_state[1].active_players.iter_mut() {
+        state.current_area = CourtArea::Backcourt;
+    }
+    let best = |game: &Game| {
+        game.options()
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+            .0
+    };
+    game.state.team_state[0].active_players[4].1.current_area = CourtArea::RestrictedAreaMiddle;
+    assert_eq!(best(&game), Choice::Shoot);
+
+    // Midway through the clock a wide open drive from midrange is the better
+    // option, until the shot clock is about to expire
+    game.state.team_state[0].active_players[4].1.current_area = CourtArea::MidrangeCenter;
+    game.state.shot_clock = Duration::from_secs(10);
+    assert!(matches!(best(&game), Choice::Drive(_)));
+    game.state.shot_clock = Duration::from_millis(800);
+    assert_eq!(best(&game), Choice::Shoot);
+}
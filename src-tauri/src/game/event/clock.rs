@@ -0,0 +1,88 @@
+use crate::game::Game;
+use crate::player::player_state::PlayerAction;
+use rand::Rng;
+use std::time::Duration;
+
+// Chance the ball handler gets a shot off as the shot clock runs out instead
+// of committing a violation
+const BEAT_SHOT_CLOCK: f32 = 0.85;
+
+// How much clock the next tick uses and whether the shot clock expires
+pub enum ClockCheck {
+    Play(Duration),
+    ShotClockViolation(Duration),
+}
+
+// Seconds the ball handler's next action takes, quicker players need less
+pub fn action_time(game: &mut Game) -> Duration {
+    let Some((player, state)) = game.player_has_ball() else {
+        return Duration::from_secs(1);
+    };
+    let (min, max) = match state.action {
+        PlayerAction::Pass => (1.5, 3.0),
+        PlayerAction::Drive => (2.0, 4.0),
+        PlayerAction::Shoot
+        | PlayerAction::ShootOfDribble
+        | PlayerAction::Layup
+        | PlayerAction::Dunk => (1.0, 2.0),
+        _ => (2.0, 5.0),
+    };
+    let speed = 1.25 - player.attributes().spd as f32 / 200.0;
+    let seconds = game.rng.gen_range(min..max) * speed;
+    Duration::from_secs_f32(seconds)
+}
+
+// Works out how long the next action takes and whether the game clock or
+// shot clock runs out during it. The shot clock is off when less game time
+// than shot clock is left.
+pub fn check_clock(game: &mut Game) -> ClockCheck {
+    let elapsed = action_time(game);
+    let shot_clock_on = game.state.shot_clock < game.state.time;
+    let limit = if shot_clock_on {
+        game.state.shot_clock
+    } else {
+        game.state.time
+    };
+    let shooting = game
+        .player_has_ball()
+        .is_some_and(|(_, s)| s.is_shot().is_some());
+    if elapsed < limit || shooting {
+        return ClockCheck::Play(elapsed.min(game.state.time));
+    }
+    // Running the clock down to zero makes the ball handler heave it up, see
+    // Game::handle_player_actions
+    if !shot_clock_on || game.rng.gen_range(0.0..1.0) < BEAT_SHOT_CLOCK {
+        ClockCheck::Play(limit)
+    } else {
+        ClockCheck::ShotClockViolation(limit)
+    }
+}
+
+#[test]
+fn test_clocks_run_by_action_time() {
+    use crate::game::Possession;
+
+    let mut game = crate::game::test_game(8);
+    let average = |game: &mut Game, index: usize| {
+        game.state.possession = Some((Possession::Home, index));
+        game.state.team_state[0].active_players[index].1.action = PlayerAction::Pass;
+        (0..200)
+            .map(|_| action_time(game).as_secs_f32())
+            .sum::<f32>()
+            / 200.0
+    };
+    // Player 4 is the fastest on the test roster, player 0 the slowest
+    assert!(average(&mut game, 4) < average(&mut game, 0));
+
+    game.state.possession = Some((Possession::Home, 0));
+    for _ in 0..10 {
+        game.state.time = Duration::from_secs(600);
+        game.state.shot_clock = Duration::from_secs(24);
+        let ClockCheck::Play(elapsed) = check_clock(&mut game) else {
+            panic!("No violation with a full shot clock");
+        };
+        game.run_clock(elapsed);
+        assert_eq!(Duration::from_secs(600) - game.state.time, elapsed);
+        assert_eq!(Duration::from_secs(24) - game.state.shot_clock, elapsed);
+    }
+}
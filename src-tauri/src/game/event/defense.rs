@@ -0,0 +1,147 @@
+use crate::game::court::{can_move_to, get_position_weight, CourtArea};
+use crate::player::player_attributes::PlayerAttributes;
+use crate::player::player_state::{PlayerAction, PlayerState};
+use crate::player::Player;
+
+// How close the defender is to the player they guard: 1.0 in the same area,
+// 0.5 one move away and 0.0 otherwise
+pub fn closeness(defender_area: CourtArea, area: CourtArea) -> f32 {
+    if defender_area == area {
+        1.0
+    } else if can_move_to(defender_area).contains(&area) {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+fn is_inside(area: CourtArea) -> bool {
+    get_position_weight(area) < 2.0
+}
+
+// Multiplier on the shooter's make chance from the pressure of their defender
+pub fn contest_factor(
+    defender: &PlayerState,
+    defender_attributes: &PlayerAttributes,
+    shooter: &PlayerState,
+) -> f32 {
+    let pressure = match defender.action {
+        PlayerAction::Contest => 0.35,
+        PlayerAction::Block => 0.3,
+        PlayerAction::DefendTight => 0.25,
+        PlayerAction::Defend => 0.15,
+        PlayerAction::DefendLoose => 0.05,
+        _ => 0.0,
+    };
+    let rating = if is_inside(shooter.current_area) {
+        defender_attributes.interior_def
+    } else {
+        defender_attributes.perimeter_def
+    };
+    let distance = closeness(defender.current_area, shooter.current_area);
+    1.0 - pressure * (0.5 + rating as f32 / 100.0) * distance * defender.energy_factor()
+}
+
+// Chance the defender blocks the shot outright. Jump shots are hard to reach,
+// length and athleticism help.
+pub fn block_chance(
+    defender: &Player,
+    defender_state: &PlayerState,
+    shooter: &Player,
+    shooter_state: &PlayerState,
+) -> f32 {
+    let base = match defender_state.action {
+        PlayerAction::Block => 0.12,
+        PlayerAction::Contest => 0.05,
+        _ => return 0.0,
+    };
+    let range = if is_inside(shooter_state.current_area) {
+        1.0
+    } else {
+        0.3
+    };
+    let size = (1.0
+        + (defender.get_height() - shooter.get_height()) as f32 / 10.0
+        + (defender.attributes().ath - shooter.attributes().ath) as f32 / 200.0)
+        .clamp(0.5, 1.5);
+    base * range
+        * size
+        * defender_state.energy_factor()
+        * (defender.attributes().block as f32 / 100.0)
+        * closeness(defender_state.current_area, shooter_state.current_area)
+}
+
+// Chance the defender takes the ball. `skill` is the handle for a drive or the
+// passer's pass rating for a pass into the defender's man.
+pub fn steal_chance(
+    defender: &PlayerState,
+    defender_attributes: &PlayerAttributes,
+    skill: i32,
+) -> f32 {
+    let base = match defender.action {
+        PlayerAction::Steal => 0.1,
+        PlayerAction::DefendTight => 0.04,
+        PlayerAction::Defend => 0.015,
+        _ => return 0.0,
+    };
+    base * (1.0 + (defender_attributes.steal - skill) as f32 / 100.0).max(0.2)
+        * defender.energy_factor()
+}
+
+#[test]
+fn test_strong_defender_blocks_and_steals() {
+    use crate::game::event::game_event::GameEventKind;
+    use crate::game::{test_game, Game, Possession};
+
+    let mut game = test_game(9);
+    let d = game.defender_of(Possession::Home, 0);
+    let id = game.state.team_state[1].active_players[d].0.get_id().ok();
+    // A big, quick-handed stopper on a weak ball handler
+    game.state.team_state[1].active_players[d].0 = Player::new(
+        id,
+        "Stopper".to_string(),
+        "Away".to_string(),
+        "C".to_string(),
+        28,
+        7,
+        260,
+        PlayerAttributes {
+            block: 99,
+            steal: 99,
+            ath: 99,
+            durability: 99,
+            ..PlayerAttributes::new()
+        },
+    );
+    let attempt = |game: &mut Game, offense: PlayerAction, defense: PlayerAction| {
+        game.state.possession = Some((Possession::Home, 0));
+        game.queued_events.clear();
+        let handler = &mut game.state.team_state[0].active_players[0].1;
+        handler.current_area = CourtArea::RestrictedAreaMiddle;
+        handler.action = offense;
+        let stopper = &mut game.state.team_state[1].active_players[d].1;
+        stopper.current_area = CourtArea::RestrictedAreaMiddle;
+        stopper.action = defense;
+        game.handle_player_actions().unwrap();
+        game.queued_events.clone()
+    };
+    let stats = |game: &mut Game| game.player_stats_mut(Possession::Away, d).cloned().unwrap();
+
+    let blocked = (0..100).any(|_| {
+        let events = attempt(&mut game, PlayerAction::Layup, PlayerAction::Block);
+        events
+            .iter()
+            .any(|e| matches!(e.kind, GameEventKind::Block { .. }))
+    });
+    assert!(blocked);
+    assert!(stats(&mut game).blocks() >= 1.0);
+
+    let stolen = (0..100).any(|_| {
+        let events = attempt(&mut game, PlayerAction::Drive, PlayerAction::Steal);
+        events
+            .iter()
+            .any(|e| matches!(e.kind, GameEventKind::Steal { .. }))
+    });
+    assert!(stolen);
+    assert!(stats(&mut game).steals() >= 1.0);
+}
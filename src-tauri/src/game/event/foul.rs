@@ -0,0 +1,125 @@
+use crate::game::court::get_position_weight;
+use crate::game::event::free_throw;
+use crate::game::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+use crate::player::player_attributes::PlayerAttributes;
+use crate::player::player_state::{PlayerAction, PlayerState};
+use std::time::Duration;
+
+// Shots taken through contact go in less often
+pub const FOULED_SHOT_FACTOR: f32 = 0.5;
+
+// Chance that the defender fouls their matchup this tick
+pub fn foul_chance(
+    defender: &PlayerState,
+    defender_attributes: &PlayerAttributes,
+    offense: &PlayerState,
+    offense_attributes: &PlayerAttributes,
+    shooting: bool,
+) -> f32 {
+    let base = match defender.action {
+        PlayerAction::Foul => 0.12,
+        PlayerAction::Block if shooting => 0.08,
+        PlayerAction::Steal => 0.04,
+        PlayerAction::DefendTight => 0.02,
+        PlayerAction::Defend => 0.01,
+        _ => 0.0,
+    };
+    // Smart defenders pick their spots, strong and athletic players draw contact
+    let discipline = 1.5 - defender_attributes.intelligence as f32 / 100.0;
+    let contact = 0.75 + (offense_attributes.strength + offense_attributes.ath) as f32 / 400.0;
+    // Attacking the rim draws more fouls than settling for jumpers
+    let inside = if get_position_weight(offense.current_area) < 2.0 {
+        1.5
+    } else {
+        1.0
+    };
+    base * discipline * contact * inside
+}
+
+// The defender matched up with the active player at `index` of `team` commits
+// a foul. `shot` is (points, made) for a shooting foul. Queues the foul and
+// everything it leads to and settles the possession.
+pub fn resolve_foul(
+    game: &mut Game,
+    team: Possession,
+    index: usize,
+    shot: Option<(u8, bool)>,
+) -> Result<(), String> {
+    let defense = team.other();
+    let defender = game.defender_of(team, index);
+    let player = game.state.team_state[defense.index()].active_players[defender]
+        .0
+        .get_id()
+        .unwrap_or_default();
+    let fouled = game.state.team_state[team.index()].active_players[index]
+        .0
+        .get_id()
+        .unwrap_or_default();
+    if let Some(stats) = game.player_stats_mut(defense, defender) {
+        stats.record_foul();
+    }
+    let team_fouls = match defense {
+        Possession::Home => {
+            game.state.fouls.0 += 1;
+            game.state.fouls.0
+        }
+        Possession::Away => {
+            game.state.fouls.1 += 1;
+            game.state.fouls.1
+        }
+    };
+    let event = GameEvent::new(
+        GameEventKind::Foul {
+            player,
+            fouled,
+            shooting: shot.is_some(),
+        },
+        game,
+    );
+    game.queued_events.push_back(event);
+    game.state.last_pass = None;
+
+    let fouls = game
+        .player_stats_mut(defense, defender)
+        .map(|s| s.personal_fouls())
+        .unwrap_or_default();
+    if fouls >= game.rules.foul_out_limit {
+        if let Some(substitution) = game.disqualify(defense, defender) {
+            game.queued_events.push_back(substitution);
+        }
+    }
+
+    let (attempts, one_and_one) = match shot {
+        // And-one
+        Some((_, true)) => (1, false),
+        Some((points, false)) => (points, false),
+        None => game.rules.bonus_free_throws(team_fouls),
+    };
+    if attempts > 0 {
+        free_throw::shoot_free_throws(game, team, index, attempts, one_and_one)?;
+    } else {
+        // Side out, the offense keeps the ball with at least the reset value
+        // on the shot clock
+        let reset = Duration::from_secs(game.rules.shot_clock_reset);
+        game.state.shot_clock = game.state.shot_clock.max(reset);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_foul_out() {
+    let mut game = crate::game::test_game(5);
+    let fouler = game.state.team_state[1].active_players[0].0.get_id();
+    game.state.possession = Some((Possession::Home, 0));
+
+    let limit = game.rules.foul_out_limit;
+    for _ in 0..limit {
+        resolve_foul(&mut game, Possession::Home, 0, None).unwrap();
+        game.state.possession = Some((Possession::Home, 0));
+    }
+    let away = &game.state.team_state[1];
+    assert_ne!(away.active_players[0].0.get_id(), fouler);
+    assert_eq!(away.bench.1[0].get_id(), fouler);
+    assert_eq!(game.state.fouls.1, limit);
+}
@@ -0,0 +1,56 @@
+use crate::game::event::rebound;
+use crate::game::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+use rand::Rng;
+
+// Shoots `attempts` free throws for the active player at `index` and queues an
+// event for each. The ball goes to the other team after a make, a miss on the
+// last attempt is live and gets rebounded. On a one-and-one a missed first
+// attempt is live too.
+pub fn shoot_free_throws(
+    game: &mut Game,
+    team: Possession,
+    index: usize,
+    attempts: u8,
+    one_and_one: bool,
+) -> Result<(), String> {
+    let (shooter, chance) = {
+        let player = &game.state.team_state[team.index()].active_players[index].0;
+        (
+            player.get_id().unwrap_or_default(),
+            player.attributes().free_throw_chance(),
+        )
+    };
+
+    let mut made = false;
+    for attempt in 1..=attempts {
+        made = game.rng.gen_range(0.0..1.0) < chance;
+        if let Some(stats) = game.player_stats_mut(team, index) {
+            stats.record_free_throw(made);
+        }
+        if made {
+            game.add_points(team, 1);
+        }
+        let event = GameEvent::new(
+            GameEventKind::FreeThrow {
+                shooter,
+                attempt,
+                attempts,
+                made,
+            },
+            game,
+        );
+        game.queued_events.push_back(event);
+        if one_and_one && !made {
+            break;
+        }
+    }
+
+    if made {
+        let inbounder = game.ball_handler(team.other());
+        game.change_possession(Some((team.other(), inbounder)));
+        Ok(())
+    } else {
+        rebound::rebound_miss(game, team)
+    }
+}
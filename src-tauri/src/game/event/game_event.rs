@@ -1,120 +1,347 @@
+use crate::game::court::CourtArea;
+use crate::game::event::clock::{self, ClockCheck};
+use crate::game::period::PeriodPhase;
 use crate::game::Game;
 use crate::game::Possession;
-use rand::Rng;
+use crate::player::injury::InjuryType;
+use crate::player::player_state::PlayerAction;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TurnoverReason {
+    ShotClock,
+    Steal,
+    BadPass,
+    OutOfBounds,
+    Backcourt,
+    OffensiveFoul,
+}
+
+// What happened, with player ids and court areas. Players are referred to by
+// their database id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GameEventKind {
+    JumpBall {
+        winner: Possession,
+        jumper: i64,
+        receiver: i64,
+    },
+    Pass {
+        from: i64,
+        to: i64,
+        from_area: CourtArea,
+        to_area: CourtArea,
+    },
+    Drive {
+        player: i64,
+        area: CourtArea,
+    },
+    // The ball handler kept the ball without passing, driving or shooting
+    Hold {
+        player: i64,
+        area: CourtArea,
+    },
+    ShotAttempt {
+        shooter: i64,
+        action: PlayerAction,
+        area: CourtArea,
+        points: u8,
+        made: bool,
+    },
+    Rebound {
+        player: i64,
+        offensive: bool,
+    },
+    // Attempt `attempt` of `attempts` awarded free throws
+    FreeThrow {
+        shooter: i64,
+        attempt: u8,
+        attempts: u8,
+        made: bool,
+    },
+    Turnover {
+        player: i64,
+        reason: TurnoverReason,
+    },
+    Steal {
+        player: i64,
+        from: i64,
+    },
+    Block {
+        player: i64,
+        shooter: i64,
+    },
+    Foul {
+        player: i64,
+        fouled: i64,
+        shooting: bool,
+    },
+    // Both players have their hands on the ball
+    HeldBall {
+        player: i64,
+        opponent: i64,
+    },
+    PeriodStart {
+        period: u8,
+    },
+    PeriodEnd {
+        period: u8,
+    },
+    GameEnd,
+    Substitution {
+        team: Possession,
+        player_in: i64,
+        player_out: i64,
+    },
+    Timeout {
+        team: Possession,
+    },
+    Injury {
+        player: i64,
+        injury: InjuryType,
+    },
+    // Events stored before events were typed, only the text is known
+    Narration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameEvent {
+    pub kind: GameEventKind,
+    // Narration of kind for the play-by-play
     pub action: String,
     pub time: String,
     pub period: u8,
     pub possession: Option<Possession>,
 }
 
+impl GameEventKind {
+    // Whether play stops after this event, which is when substitutions happen
+    pub fn is_dead_ball(&self) -> bool {
+        match self {
+            GameEventKind::ShotAttempt { made, .. } => *made,
+            GameEventKind::FreeThrow {
+                attempt,
+                attempts,
+                made,
+                ..
+            } => *made && attempt == attempts,
+            GameEventKind::Turnover { reason, .. } => *reason != TurnoverReason::Steal,
+            GameEventKind::Foul { .. }
+            | GameEventKind::HeldBall { .. }
+            | GameEventKind::Injury { .. }
+            | GameEventKind::PeriodEnd { .. }
+            | GameEventKind::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn narrate(&self, game: &Game) -> String {
+        match self {
+            GameEventKind::JumpBall {
+                winner,
+                jumper,
+                receiver,
+            } => format!(
+                "Jump Ball won for {} by {}. {} has the ball.",
+                winner,
+                game.player_name(*jumper),
+                game.player_name(*receiver)
+            ),
+            GameEventKind::Pass {
+                from,
+                to,
+                from_area,
+                to_area,
+            } => format!(
+                "{} passes to {} from {:?} to {:?}",
+                game.player_name(*from),
+                game.player_name(*to),
+                from_area,
+                to_area
+            ),
+            GameEventKind::Drive { player, area } => {
+                format!("{} drives to {:?}", game.player_name(*player), area)
+            }
+            GameEventKind::Hold { player, area } => {
+                format!("{} holds the ball at {:?}", game.player_name(*player), area)
+            }
+            GameEventKind::ShotAttempt {
+                shooter,
+                action,
+                area,
+                made,
+                ..
+            } => format!(
+                "{} {:?} from {:?} and {} it!",
+                game.player_name(*shooter),
+                action,
+                area,
+                if *made { "makes" } else { "misses" }
+            ),
+            GameEventKind::Rebound { player, offensive } => format!(
+                "{} grabs the {} rebound",
+                game.player_name(*player),
+                if *offensive { "offensive" } else { "defensive" }
+            ),
+            GameEventKind::FreeThrow {
+                shooter,
+                attempt,
+                attempts,
+                made,
+            } => format!(
+                "{} {} free throw {} of {}",
+                game.player_name(*shooter),
+                if *made { "makes" } else { "misses" },
+                attempt,
+                attempts
+            ),
+            GameEventKind::Turnover { player, reason } => {
+                format!("Turnover by {} ({:?})", game.player_name(*player), reason)
+            }
+            GameEventKind::Steal { player, from } => format!(
+                "{} steals the ball from {}",
+                game.player_name(*player),
+                game.player_name(*from)
+            ),
+            GameEventKind::Block { player, shooter } => format!(
+                "{} blocks {}",
+                game.player_name(*player),
+                game.player_name(*shooter)
+            ),
+            GameEventKind::Foul {
+                player,
+                fouled,
+                shooting,
+            } => format!(
+                "{} fouls {}{}",
+                game.player_name(*player),
+                game.player_name(*fouled),
+                if *shooting { " on the shot" } else { "" }
+            ),
+            GameEventKind::HeldBall { player, opponent } => format!(
+                "Held ball between {} and {}",
+                game.player_name(*player),
+                game.player_name(*opponent)
+            ),
+            GameEventKind::PeriodStart { period } => {
+                format!("Start of {}", game.period_name(*period))
+            }
+            GameEventKind::PeriodEnd { period } => {
+                if *period == game.rules.periods {
+                    "End of Regulation".to_string()
+                } else if *period == game.rules.halftime_period() {
+                    "Halftime".to_string()
+                } else {
+                    format!("End of {}", game.period_name(*period))
+                }
+            }
+            GameEventKind::GameEnd => "End of Game".to_string(),
+            GameEventKind::Substitution {
+                team,
+                player_in,
+                player_out,
+            } => format!(
+                "{} substitution: {} in for {}",
+                team,
+                game.player_name(*player_in),
+                game.player_name(*player_out)
+            ),
+            GameEventKind::Timeout { team } => format!("Timeout {}", team),
+            GameEventKind::Injury { player, injury } => {
+                format!("{} is injured ({})", game.player_name(*player), injury)
+            }
+            GameEventKind::Narration => String::new(),
+        }
+    }
+}
+
 impl GameEvent {
-    pub fn new(
-        action: String,
-        time: String,
-        period: u8,
-        possession: Option<Possession>,
-    ) -> GameEvent {
+    // Stamps the kind with the game's current clock, period and possession
+    pub fn new(kind: GameEventKind, game: &Game) -> GameEvent {
         GameEvent {
-            action,
-            time,
-            period,
-            possession,
+            action: kind.narrate(game),
+            kind,
+            time: game.get_time(),
+            period: game.state.period,
+            possession: game.state.possession.map(|(team, _)| team),
         }
     }
 
     pub fn is_game_end(&self) -> bool {
-        self.action == "End of Game"
+        self.kind == GameEventKind::GameEnd
+    }
+
+    pub fn write_to_db(
+        &self,
+        game_id: i64,
+        sequence: usize,
+        conn: &Connection,
+    ) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO game_events (game_id, sequence, kind, action, time, period, possession) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                game_id,
+                sequence,
+                serde_json::to_string(&self.kind).expect("Event kinds should serialize"),
+                self.action,
+                self.time,
+                self.period,
+                self.possession.map(|p| p.to_string()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_game_events_from_db(
+        game_id: i64,
+        db: &Connection,
+    ) -> Result<Vec<GameEvent>, rusqlite::Error> {
+        let mut stmt = db.prepare(
+            "SELECT kind, action, time, period, possession FROM game_events WHERE game_id = ? ORDER BY sequence",
+        )?;
+        let events = stmt
+            .query_map([game_id], |row| {
+                let kind: Option<String> = row.get(0)?;
+                let possession: Option<String> = row.get(4)?;
+                Ok(GameEvent {
+                    kind: kind
+                        .and_then(|k| serde_json::from_str(&k).ok())
+                        .unwrap_or(GameEventKind::Narration),
+                    action: row.get(1)?,
+                    time: row.get(2)?,
+                    period: row.get(3)?,
+                    possession: match possession.as_deref() {
+                        Some("Home") => Some(Possession::Home),
+                        Some("Away") => Some(Possession::Away),
+                        _ => None,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<GameEvent>, _>>()?;
+        Ok(events)
     }
 
     pub fn generate_next_game_event(game: &mut Game) -> Result<GameEvent, String> {
         // Generate next event
-        if game.state.time.as_secs() > 0 {
-            //Default
-            let event = game.handle_player_actions();
-            //Generate random number between 1 and 24 float
-            let mut rng = rand::thread_rng();
-            let max = f32::min(3.0, game.state.time.as_secs_f32());
-            let random = rng.gen_range(1.0..max);
-            if random > game.state.shot_clock.as_secs_f32() {
-                println!("Shot clock ran out. Turnover!");
-                match game.state.possession {
-                    Some((Possession::Home, _)) => {
-                        game.change_possession(Some((Possession::Away, 3)));
+        if game.state.phase == PeriodPhase::Live && !game.state.time.is_zero() {
+            match clock::check_clock(game) {
+                ClockCheck::Play(elapsed) => game.run_clock(elapsed),
+                ClockCheck::ShotClockViolation(elapsed) => {
+                    game.run_clock(elapsed);
+                    let turnover = game.shot_clock_turnover();
+                    if let Some((team, _)) = game.state.possession {
+                        let inbounder = game.ball_handler(team.other());
+                        game.change_possession(Some((team.other(), inbounder)));
+                        game.update_player_states()?;
                     }
-                    Some((Possession::Away, _)) => {
-                        game.change_possession(Some((Possession::Home, 3)));
-                    }
-                    None => {
-                        //Jump ball
-                    }
-                }
-                return Ok(GameEvent::new(
-                    "Turnover".to_string(),
-                    game.get_time(),
-                    0,
-                    None,
-                ));
-            } else {
-                game.state.shot_clock -= Duration::from_secs_f32(random)
-            }
-            game.state.time -= Duration::from_secs_f32(random);
-
-            return Ok(event.unwrap());
-        //    }
-        } else if game.state.period >= 4 {
-            if game.state.score.0 == game.state.score.1 {
-                //Overtime
-                game.state.period += 1;
-                game.state.time = Duration::from_secs(300);
-                let event = game.handle_player_actions();
-                //Generate random number between 1 and 24 float
-                let mut rng = rand::thread_rng();
-                let max = f32::min(6.0, game.state.time.as_secs_f32());
-                let random = rng.gen_range(1.0..max);
-                if random > game.state.shot_clock.as_secs_f32() {
-                    println!("Shot clock ran out. Turnover!");
-                    match game.state.possession {
-                        Some((Possession::Home, _)) => {
-                            game.change_possession(Some((Possession::Away, 3)));
-                        }
-                        Some((Possession::Away, _)) => {
-                            game.change_possession(Some((Possession::Home, 3)));
-                        }
-                        None => {
-                            //Jump ball
-                        }
-                    }
-                } else {
-                    game.state.shot_clock -= Duration::from_secs_f32(random)
+                    return turnover.ok_or("No player has the ball".to_string());
                 }
-                game.state.time -= Duration::from_secs_f32(random);
-                game.events.push(event.clone().unwrap());
-
-                return Ok(event.unwrap());
             }
-            // Game is over
-            game.state.time = Duration::from_secs(0);
-            return Ok(GameEvent::new(
-                "End of Game".to_string(),
-                game.get_time(),
-                0,
-                None,
-            ));
+            game.handle_player_actions()
         } else {
-            // End of qtr
-            game.state.period += 1;
-            game.state.time = Duration::from_secs(720);
-            return Ok(GameEvent::new(
-                "End of Quarter".to_string(),
-                game.get_time(),
-                0,
-                None,
-            ));
+            game.next_period_event()
         }
     }
 }
@@ -0,0 +1,80 @@
+use crate::game::event::jump_ball;
+use crate::game::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+
+// Chance a defender going for the ball ties up the ball handler instead of
+// stealing it, as a share of their steal chance
+pub const TIE_UP_SHARE: f32 = 0.3;
+// Chance a rebound ends up with a player of each team holding the ball
+pub const REBOUND_HELD_BALL: f32 = 0.01;
+
+// The player at `index` of `team` has the ball tied up by their matchup.
+// Queues the held ball, then the ball goes to the team the arrow points at or
+// to whoever wins a jump ball between the two, depending on the rules.
+pub fn resolve_held_ball(game: &mut Game, team: Possession, index: usize) -> Result<(), String> {
+    let defender = game.defender_of(team, index);
+    let player = |team: Possession, index: usize| {
+        game.state.team_state[team.index()].active_players[index]
+            .0
+            .get_id()
+            .unwrap_or_default()
+    };
+    let event = GameEvent::new(
+        GameEventKind::HeldBall {
+            player: player(team, index),
+            opponent: player(team.other(), defender),
+        },
+        game,
+    );
+    game.queued_events.push_back(event);
+    game.state.last_pass = None;
+
+    if game.rules.possession_arrow {
+        if let Some(arrow) = game.use_arrow() {
+            // The team that had the ball keeps what is left on the shot clock
+            let inbounder = if arrow == team {
+                index
+            } else {
+                game.ball_handler(arrow)
+            };
+            game.change_possession(Some((arrow, inbounder)));
+            let _ = game.update_player_states();
+            return Ok(());
+        }
+    }
+    let (home, away) = match team {
+        Possession::Home => (index, defender),
+        Possession::Away => (defender, index),
+    };
+    let jump_ball = jump_ball::jump_between(game, home, away)?;
+    game.queued_events.push_back(jump_ball);
+    Ok(())
+}
+
+#[test]
+fn test_held_balls_alternate_with_arrow() {
+    let mut game = crate::game::test_game(9);
+    game.rules = crate::game::rules::RuleSet::fiba();
+    game.state.arrow = Some(Possession::Away);
+    game.state.possession = Some((Possession::Home, 2));
+
+    resolve_held_ball(&mut game, Possession::Home, 2).unwrap();
+    assert!(matches!(game.state.possession, Some((Possession::Away, _))));
+    assert_eq!(game.state.arrow, Some(Possession::Home));
+
+    // The team with the ball keeps it when the arrow points their way
+    game.state.possession = Some((Possession::Home, 2));
+    resolve_held_ball(&mut game, Possession::Home, 2).unwrap();
+    assert_eq!(game.state.possession, Some((Possession::Home, 2)));
+    assert_eq!(game.state.arrow, Some(Possession::Away));
+
+    // Without the arrow the two players jump for it
+    game.rules = crate::game::rules::RuleSet::nba();
+    game.queued_events.clear();
+    resolve_held_ball(&mut game, Possession::Home, 2).unwrap();
+    assert!(matches!(
+        game.queued_events[1].kind,
+        GameEventKind::JumpBall { .. }
+    ));
+    assert_eq!(game.state.arrow, Some(Possession::Away));
+}
@@ -0,0 +1,50 @@
+use crate::game::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+use crate::player::injury::Injury;
+use crate::player::player_state::{PlayerAction, PlayerState, MAX_ENERGY};
+use crate::player::Player;
+use rand::Rng;
+
+// Chance per player per tick before contact, durability and fatigue
+const BASE_INJURY_CHANCE: f32 = 0.000004;
+
+pub fn injury_chance(player: &Player, state: &PlayerState) -> f32 {
+    let contact = match state.action {
+        PlayerAction::Dunk | PlayerAction::Foul => 3.0,
+        PlayerAction::Drive | PlayerAction::Layup | PlayerAction::Block => 2.0,
+        PlayerAction::Rebound | PlayerAction::BallScreen | PlayerAction::OffBallScreen => 1.5,
+        _ => 1.0,
+    };
+    let durability = 1.5 - player.attributes().durability as f32 / 100.0;
+    // Tired players get hurt up to twice as often
+    let fatigue = 2.0 - state.energy / MAX_ENERGY;
+    BASE_INJURY_CHANCE * contact * durability * fatigue
+}
+
+// Rolls for an injury on everyone on the floor. Injured players are recorded
+// on the game and forced out, with the events queued.
+pub fn check_injuries(game: &mut Game) {
+    for team in [Possession::Home, Possession::Away] {
+        for index in 0..5 {
+            let (player, state) = &game.state.team_state[team.index()].active_players[index];
+            let chance = injury_chance(player, state);
+            let player_id = player.get_id().unwrap_or_default();
+            if game.rng.gen_range(0.0..1.0) >= chance {
+                continue;
+            }
+            let injury = Injury::generate(player_id, &mut game.rng);
+            let event = GameEvent::new(
+                GameEventKind::Injury {
+                    player: player_id,
+                    injury: injury.injury,
+                },
+                game,
+            );
+            game.queued_events.push_back(event);
+            game.injuries.push(injury);
+            if let Some(substitution) = game.disqualify(team, index) {
+                game.queued_events.push_back(substitution);
+            }
+        }
+    }
+}
@@ -1,72 +1,70 @@
-use crate::game::game_event::GameEvent;
+use crate::game::game_event::{GameEvent, GameEventKind};
 use crate::game::{Game, Possession};
 use crate::util::rng::rng;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-pub fn generate_jump_ball(game: &mut Game) -> Result<(), String> {
-    // Collect the necessary data without holding references
-    let home_best = game.state.team_state[0]
-        .active_players
-        .iter()
-        .map(|p| {
-            (
-                p.0.attributes().ath + p.0.get_height(),
-                p.0.first_name.clone(),
-                p.0.last_name.clone(),
-            )
-        })
-        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
-        .ok_or("No home players")?;
-
-    let away_best = game.state.team_state[1]
-        .active_players
-        .iter()
-        .map(|p| {
-            (
-                p.0.attributes().ath + p.0.get_height(),
-                p.0.first_name.clone(),
-                p.0.last_name.clone(),
-            )
-        })
-        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
-        .ok_or("No away players")?;
+// Jumps the best leaper of each team for the ball. The first one of the game
+// is the opening tip, its loser gets the possession arrow.
+pub fn generate_jump_ball(game: &mut Game) -> Result<GameEvent, String> {
+    let best_jumper = |team: Possession| {
+        game.state.team_state[team.index()]
+            .active_players
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, p)| p.0.attributes().ath + p.0.get_height())
+            .map(|(i, _)| i)
+    };
+    let home = best_jumper(Possession::Home).ok_or("No home players")?;
+    let away = best_jumper(Possession::Away).ok_or("No away players")?;
 
-    let winner = rng(home_best.0, away_best.0);
-    println!("Winner: {}", winner);
+    let event = jump_between(game, home, away)?;
+    if game.state.opening_tip.is_none() {
+        if let GameEventKind::JumpBall { winner, .. } = event.kind {
+            game.state.opening_tip = Some(winner);
+            game.state.arrow = Some(winner.other());
+        }
+    }
+    Ok(event)
+}
 
-    let mut rng = thread_rng();
+// Jump ball between the home player at `home` and the away player at `away`,
+// the winner taps it to a random teammate. Returns the event for the caller
+// to hand out.
+pub fn jump_between(game: &mut Game, home: usize, away: usize) -> Result<GameEvent, String> {
+    let jump = |team: Possession, index: usize| {
+        let player = &game.state.team_state[team.index()].active_players[index].0;
+        (
+            player.attributes().ath + player.get_height(),
+            player.get_id().unwrap_or_default(),
+        )
+    };
+    let home_jumper = jump(Possession::Home, home);
+    let away_jumper = jump(Possession::Away, away);
 
-    let (possession, winning_team_state, best_jumper) = if winner == 0 {
-        (Possession::Home, &mut game.state.team_state[0], home_best)
+    let winner = rng(home_jumper.0, away_jumper.0, &mut game.rng);
+    let (possession, jumper) = if winner == 0 {
+        (Possession::Home, home_jumper.1)
     } else {
-        (Possession::Away, &mut game.state.team_state[1], away_best)
+        (Possession::Away, away_jumper.1)
     };
 
-    let player_index = rng.gen_range(0..winning_team_state.active_players.len());
+    let player_index = game.rng.gen_range(0..5);
     game.change_possession(Some((possession, player_index)));
+    // The tip can go anywhere, whoever gets it brings the ball up again
+    game.state.frontcourt = false;
 
-    let has_ball = game.player_has_ball();
-    match has_ball {
-        Some((player, _)) => {
-            let event = GameEvent::new(
-                format!(
-                    "Jump Ball won for {} by {} {}. {} {} has the ball.",
-                    if winner == 0 { "Home" } else { "Away" },
-                    best_jumper.1,
-                    best_jumper.2,
-                    player.first_name,
-                    player.last_name
-                ),
-                game.get_time(),
-                1,
-                Some(possession),
-            );
-            game.events.push(event);
-        }
-        None => {
-            return Ok(());
-        }
-    }
-    game.update_player_states();
-    Ok(())
+    let receiver = game
+        .player_has_ball()
+        .map(|(player, _)| player.get_id().unwrap_or_default())
+        .ok_or("Nobody has the ball after the jump ball")?;
+    let event = GameEvent::new(
+        GameEventKind::JumpBall {
+            winner: possession,
+            jumper,
+            receiver,
+        },
+        game,
+    );
+    game.update_player_states()?;
+    Ok(event)
 }
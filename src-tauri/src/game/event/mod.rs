@@ -1,2 +1,10 @@
+pub mod clock;
+pub mod defense;
+pub mod foul;
+pub mod free_throw;
 pub mod game_event;
+pub mod held_ball;
+pub mod injury;
 pub mod jump_ball;
+pub mod pass;
+pub mod rebound;
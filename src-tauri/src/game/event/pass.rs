@@ -0,0 +1,171 @@
+use crate::game::court::{find_path, CourtArea};
+use crate::game::decision::misjudgement;
+use crate::game::event::defense;
+use crate::game::{Game, Possession};
+use rand::Rng;
+
+// Each step the ball travels gives the defense more time to recover
+const PASS_STEP_FACTOR: f32 = 0.95;
+// Share of the receiver's value lost with a defender right on them
+const CROWDED_FACTOR: f32 = 0.3;
+// Chance of throwing it away on a one step pass for a 0 rated passer
+const BAD_PASS_CHANCE: f32 = 0.04;
+
+pub enum PassOutcome {
+    Complete,
+    // Picked off by the defender at this active index
+    Stolen(usize),
+    // Thrown away out of bounds
+    BadPass,
+    // Caught behind half court after the ball had crossed it
+    Backcourt,
+}
+
+// Steps the ball travels between the two areas
+fn steps(from: CourtArea, to: CourtArea) -> usize {
+    find_path(from, to).map_or(1, |path| path.len().saturating_sub(1).max(1))
+}
+
+// Defenders standing on the way from the passer to the receiver, and the
+// receiver's own defender
+fn lane_defenders(game: &Game, team: Possession, from: usize, to: usize) -> Vec<usize> {
+    let offense = &game.state.team_state[team.index()].active_players;
+    let lane =
+        find_path(offense[from].1.current_area, offense[to].1.current_area).unwrap_or_default();
+    let receiver_defender = game.defender_of(team, to);
+    game.state.team_state[team.other().index()]
+        .active_players
+        .iter()
+        .enumerate()
+        .filter(|(d, (_, state))| {
+            *d == receiver_defender || lane.iter().skip(1).any(|&a| a == state.current_area)
+        })
+        .map(|(d, _)| d)
+        .collect()
+}
+
+// Chance each lane defender picks the pass off
+fn steal_chances(game: &Game, team: Possession, from: usize, to: usize) -> Vec<(usize, f32)> {
+    let pass = game.state.team_state[team.index()].active_players[from]
+        .0
+        .attributes()
+        .pass;
+    lane_defenders(game, team, from, to)
+        .into_iter()
+        .map(|d| {
+            let (defender, state) = &game.state.team_state[team.other().index()].active_players[d];
+            (d, defense::steal_chance(state, defender.attributes(), pass))
+        })
+        .collect()
+}
+
+fn bad_pass_chance(game: &Game, team: Possession, from: usize, to: usize) -> f32 {
+    let offense = &game.state.team_state[team.index()].active_players;
+    let pass = offense[from].0.attributes().pass;
+    let steps = steps(offense[from].1.current_area, offense[to].1.current_area);
+    BAD_PASS_CHANCE * (1.0 - pass as f32 / 100.0).max(0.1) * steps as f32
+}
+
+// Whether a pass to `to` would take the ball back over half court
+fn goes_back(game: &Game, team: Possession, to: usize) -> bool {
+    game.state.frontcourt
+        && !game.state.team_state[team.index()].active_players[to]
+            .1
+            .current_area
+            .is_front_court()
+}
+
+// Chance the pass from `from` to `to` doesn't arrive
+pub fn turnover_chance(game: &Game, team: Possession, from: usize, to: usize) -> f32 {
+    if goes_back(game, team, to) {
+        return 1.0;
+    }
+    let kept = steal_chances(game, team, from, to)
+        .iter()
+        .fold(1.0, |kept, (_, chance)| kept * (1.0 - chance));
+    1.0 - kept * (1.0 - bad_pass_chance(game, team, from, to))
+}
+
+// What a pass to `to` is worth: the receiver's shot from where they stand,
+// less for defenders nearby, long passes and the risk of losing the ball
+pub fn target_value(game: &Game, team: Possession, from: usize, to: usize) -> f32 {
+    let offense = &game.state.team_state[team.index()].active_players;
+    let area = offense[to].1.current_area;
+    let crowded = game.state.team_state[team.other().index()]
+        .active_players
+        .iter()
+        .map(|(_, state)| defense::closeness(state.current_area, area))
+        .fold(0.0, f32::max);
+    let steps = steps(offense[from].1.current_area, area);
+    game.shot_value(team, to, area)
+        * (1.0 - CROWDED_FACTOR * crowded)
+        * PASS_STEP_FACTOR.powi(steps as i32)
+        * (1.0 - turnover_chance(game, team, from, to))
+}
+
+// Teammate the player at `index` passes to, the smarter the passer the more
+// often it is the best one
+pub fn pass_target(game: &mut Game, team: Possession, index: usize) -> Option<usize> {
+    let intelligence = game.state.team_state[team.index()].active_players[index]
+        .0
+        .attributes()
+        .intelligence;
+    let misjudgement = misjudgement(intelligence);
+    let values: Vec<(usize, f32)> = (0..5)
+        .filter(|&i| i != index)
+        .map(|i| (i, target_value(game, team, index, i)))
+        .collect();
+    values
+        .into_iter()
+        .map(|(i, value)| {
+            (
+                i,
+                value * (1.0 + game.rng.gen_range(-1.0..=1.0) * misjudgement),
+            )
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+// Whether the pass gets there, rolled against every lane defender and then
+// the passer's accuracy
+pub fn throw_pass(game: &mut Game, team: Possession, from: usize, to: usize) -> PassOutcome {
+    if goes_back(game, team, to) {
+        return PassOutcome::Backcourt;
+    }
+    for (defender, chance) in steal_chances(game, team, from, to) {
+        if game.rng.gen_range(0.0..1.0) < chance {
+            return PassOutcome::Stolen(defender);
+        }
+    }
+    if game.rng.gen_range(0.0..1.0) < bad_pass_chance(game, team, from, to) {
+        return PassOutcome::BadPass;
+    }
+    PassOutcome::Complete
+}
+
+#[test]
+fn test_passes_find_the_open_shooter() {
+    let mut game = crate::game::test_game(4);
+    game.state.possession = Some((Possession::Home, 0));
+    let areas = [
+        CourtArea::ThreePointLineCenter,
+        CourtArea::ThreePointLineWingLeft,
+        CourtArea::Center,
+        CourtArea::RestrictedAreaMiddle,
+        CourtArea::RestrictedAreaLeft,
+    ];
+    for (i, area) in areas.into_iter().enumerate() {
+        game.state.team_state[0].active_players[i].1.current_area = area;
+        game.state.team_state[1].active_players[i].1.current_area = area;
+    }
+    // Everyone is covered but the best finisher under the basket
+    game.state.team_state[1].active_players[4].1.current_area = CourtArea::Backcourt;
+    game.assign_matchups(Possession::Away);
+    let value = |to: usize| target_value(&game, Possession::Home, 0, to);
+    let best = (1..5)
+        .max_by(|&a, &b| value(a).total_cmp(&value(b)))
+        .unwrap();
+    assert_eq!(best, 4);
+    assert!(turnover_chance(&game, Possession::Home, 0, 4) > 0.0);
+}
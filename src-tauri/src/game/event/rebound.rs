@@ -0,0 +1,120 @@
+use crate::game::court::get_position_weight;
+use crate::game::event::held_ball;
+use crate::game::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+use crate::player::player_state::PlayerAction;
+use crate::util::rng::rng_arr;
+use rand::Rng;
+use std::time::Duration;
+
+// Defenders start between their man and the basket, so they win most boards
+const BOX_OUT_FACTOR: f32 = 3.0;
+// Offensive players crashing the glass instead of getting back
+const CRASH_FACTOR: f32 = 2.0;
+
+// How much being close to the rim matters, from get_position_weight
+fn proximity(area_weight: f32) -> f32 {
+    if area_weight.is_finite() {
+        (6.0 - area_weight).max(0.5)
+    } else {
+        0.0
+    }
+}
+
+// Picks who secures a missed shot out of all ten players and credits the
+// rebound. Returns the team and active player index that now has the ball.
+pub fn generate_rebound(game: &mut Game, shooting_team: Possession) -> (Possession, usize) {
+    let mut candidates = Vec::with_capacity(10);
+    let mut weights = Vec::with_capacity(10);
+    for team in [shooting_team, shooting_team.other()] {
+        let offensive = team == shooting_team;
+        for (index, (player, state)) in game.state.team_state[team.index()]
+            .active_players
+            .iter()
+            .enumerate()
+        {
+            let attributes = player.attributes();
+            let rating = if offensive {
+                attributes.off_rebound
+            } else {
+                attributes.def_rebound
+            };
+            let mut weight = (rating + attributes.strength) as f32
+                * proximity(get_position_weight(state.current_area));
+            if !offensive {
+                weight *= BOX_OUT_FACTOR;
+            } else if state.action == PlayerAction::Rebound {
+                weight *= CRASH_FACTOR;
+            }
+            candidates.push((team, index));
+            weights.push(weight.max(1.0) as i32);
+        }
+    }
+
+    let (team, index) = candidates[rng_arr(weights, &mut game.rng) as usize];
+    if let Some(stats) = game.player_stats_mut(team, index) {
+        stats.record_rebound(team == shooting_team);
+    }
+    (team, index)
+}
+
+// Gives the ball to whoever rebounds a miss and queues the rebound event. An
+// offensive rebound keeps the possession, the shot clock is topped up to the
+// reset value. Sometimes the rebounder gets tied up right away.
+pub fn rebound_miss(game: &mut Game, shooting_team: Possession) -> Result<(), String> {
+    let (team, index) = generate_rebound(game, shooting_team);
+    game.change_possession(Some((team, index)));
+    let offensive = team == shooting_team;
+    if offensive {
+        let reset = Duration::from_secs(game.rules.shot_clock_reset);
+        game.state.shot_clock = game.state.shot_clock.max(reset);
+        // A long rebound can be taken in the backcourt and brought up again
+        game.state.frontcourt = false;
+    }
+    let player = game.state.team_state[team.index()].active_players[index]
+        .0
+        .get_id()
+        .unwrap_or_default();
+    let event = GameEvent::new(GameEventKind::Rebound { player, offensive }, game);
+    game.queued_events.push_back(event);
+    if game.rng.gen_range(0.0..1.0) < held_ball::REBOUND_HELD_BALL {
+        held_ball::resolve_held_ball(game, team, index)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_defense_wins_most_rebounds() {
+    let mut game = crate::game::test_game(3);
+    let mut defensive = 0;
+    for _ in 0..200 {
+        let (team, _) = generate_rebound(&mut game, Possession::Home);
+        if team == Possession::Away {
+            defensive += 1;
+        }
+    }
+    assert!(defensive > 100);
+    assert!(defensive < 200);
+}
+
+#[test]
+fn test_offensive_rebound_tops_up_shot_clock() {
+    let mut game = crate::game::test_game(3);
+    game.state.possession = Some((Possession::Home, 0));
+    let reset = Duration::from_secs(game.rules.shot_clock_reset);
+    for remaining in [reset + Duration::from_secs(6), Duration::from_secs(3)] {
+        loop {
+            game.state.shot_clock = remaining;
+            rebound_miss(&mut game, Possession::Home).unwrap();
+            let last = game.queued_events.back().map(|e| e.kind.clone());
+            if let Some(GameEventKind::Rebound {
+                offensive: true, ..
+            }) = last
+            {
+                break;
+            }
+            game.state.possession = Some((Possession::Home, 0));
+        }
+        assert_eq!(game.state.shot_clock, remaining.max(reset));
+    }
+}
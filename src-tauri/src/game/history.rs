@@ -0,0 +1,135 @@
+use crate::player::injury::Injury;
+use crate::player::player_stats::PlayerStats;
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use super::Game;
+
+// A completed game as listed in the history, without events or box score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: i64,
+    pub home_team_id: i64,
+    pub away_team_id: i64,
+    pub score: (u16, u16),
+    pub periods: u8,
+    pub seed: u64,
+    pub played_at: String,
+}
+
+impl GameSummary {
+    pub fn get_games_from_db(db: &Connection) -> Result<Vec<GameSummary>, rusqlite::Error> {
+        let mut stmt = db.prepare(
+            "SELECT id, home_team_id, away_team_id, home_score, away_score, periods, seed, played_at
+            FROM games
+            ORDER BY id DESC",
+        )?;
+        let games = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<GameSummary>, _>>()?;
+        Ok(games)
+    }
+
+    pub fn get_game(game_id: i64, db: &Connection) -> Result<GameSummary, rusqlite::Error> {
+        let mut stmt = db.prepare(
+            "SELECT id, home_team_id, away_team_id, home_score, away_score, periods, seed, played_at
+            FROM games
+            WHERE id = ?",
+        )?;
+        stmt.query_row([game_id], Self::from_row)
+    }
+
+    fn from_row(row: &Row) -> Result<GameSummary, rusqlite::Error> {
+        Ok(GameSummary {
+            id: row.get(0)?,
+            home_team_id: row.get(1)?,
+            away_team_id: row.get(2)?,
+            score: (row.get(3)?, row.get(4)?),
+            periods: row.get(5)?,
+            // SQLite integers are signed, the seed is stored bit for bit
+            seed: row.get::<_, i64>(6)? as u64,
+            played_at: row.get(7)?,
+        })
+    }
+
+    // Stored box score split into (home, away)
+    pub fn get_box_score(
+        &self,
+        db: &Connection,
+    ) -> Result<(Vec<PlayerStats>, Vec<PlayerStats>), rusqlite::Error> {
+        let stats = PlayerStats::get_game_stats_from_db(self.id, db)?;
+        Ok(stats
+            .into_iter()
+            .partition(|s| s.team_id() == self.home_team_id))
+    }
+}
+
+impl Game {
+    // Stores the game, its play-by-play and box score in one transaction
+    pub fn write_to_db(&mut self, db: &Connection) -> Result<i64, rusqlite::Error> {
+        let tx = db.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO games (home_team_id, away_team_id, home_score, away_score, periods, seed)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.teams.0.get_id(),
+                self.teams.1.get_id(),
+                self.state.score.0,
+                self.state.score.1,
+                self.state.period,
+                self.seed as i64,
+            ],
+        )?;
+        let game_id = tx.last_insert_rowid();
+        for (sequence, event) in self.events.iter().enumerate() {
+            event.write_to_db(game_id, sequence, &tx)?;
+        }
+        for team_state in self.state.team_state.iter_mut() {
+            for stats in team_state.box_score.iter_mut() {
+                stats.write_to_db(game_id, &tx)?;
+            }
+        }
+        // Earlier injuries heal by one game before this game's are added
+        Injury::heal_after_game(self.teams.0.get_id(), self.teams.1.get_id(), &tx)?;
+        for injury in self.injuries.iter() {
+            injury.write_to_db(Some(game_id), &tx)?;
+        }
+        tx.commit()?;
+        self.id = Some(game_id);
+        Ok(game_id)
+    }
+
+    // Stores a finished game once, saving it again returns the stored id
+    pub fn save_finished(&mut self, db: &Connection) -> Result<i64, String> {
+        if let Some(id) = self.id {
+            return Ok(id);
+        }
+        if !self.events.last().is_some_and(|e| e.is_game_end()) {
+            return Err("Game has not finished".to_string());
+        }
+        self.write_to_db(db).map_err(|e| e.to_string())
+    }
+}
+
+#[test]
+fn test_game_round_trip() {
+    use crate::game::event::game_event::GameEvent;
+
+    let mut conn = Connection::open_in_memory().unwrap();
+    crate::util::migrations::migrate(&mut conn).unwrap();
+    let mut game = crate::game::test_game(5);
+    assert!(game.save_finished(&conn).is_err());
+    game.simulate_to_end().unwrap();
+    let game_id = game.save_finished(&conn).unwrap();
+    assert_eq!(game.save_finished(&conn).unwrap(), game_id);
+
+    let summary = GameSummary::get_game(game_id, &conn).unwrap();
+    assert_eq!(summary.score, game.get_score());
+    assert_eq!(summary.seed, game.get_seed());
+    assert_eq!(GameSummary::get_games_from_db(&conn).unwrap().len(), 1);
+    let events = GameEvent::get_game_events_from_db(game_id, &conn).unwrap();
+    assert_eq!(events, game.events);
+    let (home, away) = summary.get_box_score(&conn).unwrap();
+    let stored = serde_json::to_value((home, away)).unwrap();
+    assert_eq!(stored, serde_json::to_value(game.get_box_score()).unwrap());
+}
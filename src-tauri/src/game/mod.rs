@@ -3,18 +3,51 @@ use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
 pub mod court;
+pub mod decision;
 pub mod event;
-use crate::game::court::CourtArea;
+pub mod history;
+pub mod period;
+pub mod play;
+pub mod predictor;
+pub mod rotation;
+pub mod rules;
+pub mod scheme;
+pub mod timeout;
+pub mod transition;
+use crate::game::event::defense;
+use crate::game::event::foul;
 use crate::game::event::game_event;
-use crate::game::event::jump_ball;
+use crate::game::event::game_event::{GameEventKind, TurnoverReason};
+use crate::game::event::held_ball;
+use crate::game::event::injury;
+use crate::game::event::pass::{self, PassOutcome};
+use crate::game::event::rebound;
+use crate::game::period::PeriodPhase;
+use crate::game::play::ActivePlay;
+use crate::game::rotation::RotationPlan;
+use crate::game::rules::RuleSet;
+use crate::game::scheme::DefensiveScheme;
+use crate::player::injury::Injury;
 use crate::player::player_state::PlayerAction;
-use crate::player::player_state::PlayerState;
+use crate::player::player_state::{PlayerState, MAX_ENERGY};
+use crate::player::player_stats::PlayerStats;
 use crate::player::Player;
 use crate::team::Team;
-use rand::{thread_rng, Rng};
+use crate::util::rng::{self, GameRng};
+use rand::Rng;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
 use std::time::Duration;
 
+// Energy a bench player gets back per second of game time
+const BENCH_RECOVERY: f32 = 0.15;
+// Energy everyone gets back between periods and at halftime
+pub const PERIOD_BREAK_RECOVERY: f32 = 10.0;
+pub const HALFTIME_RECOVERY: f32 = 30.0;
+// Far more events than any game needs, even with many overtimes. Stops a
+// headless simulation that never reaches the final buzzer.
+const MAX_GAME_EVENTS: usize = 100_000;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum Possession {
     Home,
@@ -28,42 +61,126 @@ impl fmt::Display for Possession {
         }
     }
 }
+impl Possession {
+    // Index into GameState.team_state
+    pub fn index(&self) -> usize {
+        match self {
+            Possession::Home => 0,
+            Possession::Away => 1,
+        }
+    }
+    pub fn other(&self) -> Possession {
+        match self {
+            Possession::Home => Possession::Away,
+            Possession::Away => Possession::Home,
+        }
+    }
+}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TeamState {
     active_players: [(Player, PlayerState); 5],
+    // (available, fouled out or injured)
     bench: (Vec<Player>, Vec<Player>),
+    // One line per rostered player, starters first
+    box_score: Vec<PlayerStats>,
+    rotation: RotationPlan,
+    // Energy of bench players by player id, active players keep theirs in
+    // their PlayerState
+    resting_energy: BTreeMap<i64, f32>,
+    #[serde(default)]
+    scheme: DefensiveScheme,
+    // Active index of the offensive player each defender guards
+    #[serde(default = "default_matchups")]
+    matchups: [usize; 5],
+}
+fn default_matchups() -> [usize; 5] {
+    [0, 1, 2, 3, 4]
 }
 impl TeamState {
-    pub fn new(starters: [Player; 5], bench: Vec<Player>) -> Self {
+    pub fn new(team_id: Option<i64>, starters: [Player; 5], bench: Vec<Player>) -> Self {
+        let box_score = starters
+            .iter()
+            .chain(bench.iter())
+            .filter_map(|p| p.get_id().ok())
+            .map(|player_id| PlayerStats::empty(None, player_id, team_id.unwrap_or_default()))
+            .collect();
         Self {
+            rotation: RotationPlan::new(&starters, &bench),
             active_players: starters.map(|p| (p, PlayerState::new(None))),
-            bench: (Vec::new(), Vec::new()),
+            bench: (bench, Vec::new()),
+            box_score,
+            resting_energy: BTreeMap::new(),
+            scheme: DefensiveScheme::default(),
+            matchups: default_matchups(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct GameState {
+pub struct GameState {
     time: Duration,
     shot_clock: Duration,
     period: u8,
+    #[serde(default)]
+    phase: PeriodPhase,
+    // Winner of the opening tip
+    #[serde(default)]
+    opening_tip: Option<Possession>,
+    // Team that gets the next alternating possession, starts with the loser
+    // of the opening tip
+    #[serde(default)]
+    arrow: Option<Possession>,
     possession: Option<(Possession, usize)>,
     team_state: [TeamState; 2],
     fouls: (u8, u8),
+    // Remaining, see Game::available_timeouts for what can be used now
     timeouts: (u8, u8),
-    score: (u8, u8),
+    score: (u16, u16),
+    // Team on an unanswered scoring run and its points
+    #[serde(default)]
+    run: Option<(Possession, u16)>,
+    // Index of the last player to pass on the team with the ball, for assists
+    last_pass: Option<usize>,
+    // Play the offense is running
+    #[serde(default)]
+    play: Option<ActivePlay>,
+    // The ball has been past half court this possession, it can't go back
+    #[serde(default)]
+    frontcourt: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
+    // Set once the finished game is written to the database
+    id: Option<i64>,
     teams: (Team, Team),
     events: Vec<game_event::GameEvent>,
+    // Follow-up events from the last action (e.g. the rebound after a miss),
+    // handed out one per tick before the clock moves again
+    #[serde(default)]
+    queued_events: VecDeque<GameEvent>,
     state: GameState,
+    // Injuries suffered in this game, stored with it
+    #[serde(default)]
+    injuries: Vec<Injury>,
+    // Timeout called by the user, not taken yet
+    #[serde(default)]
+    pending_timeout: Option<Possession>,
+    #[serde(default)]
+    rules: RuleSet,
     sim: bool,
+    seed: u64,
+    // Only the seed is serialized, a deserialized game draws from a fresh stream
+    #[serde(skip, default = "rng::from_entropy")]
+    rng: GameRng,
 }
 
 impl Game {
-    pub fn new(db: &Connection) -> Result<Game, rusqlite::Error> {
+    pub fn new(
+        db: &Connection,
+        seed: Option<u64>,
+        rules: RuleSet,
+    ) -> Result<Game, rusqlite::Error> {
         let teams = Team::get_teams_from_db(db);
         match teams {
             Ok(teams) => {
@@ -79,30 +196,67 @@ impl Game {
                         .expect("Error getting starting lineups"),
                     teams[1].get_bench(db).expect("Error getting bench."),
                 );
-                let home_state = TeamState::new(home_players.0, home_players.1);
-                let away_state = TeamState::new(away_players.0, away_players.1);
-                let game = Game {
-                    teams: (teams[0].clone(), teams[1].clone()),
-                    state: GameState {
-                        period: 1,
-                        shot_clock: Duration::from_secs(24),
-                        possession: None,
-                        score: (0, 0),
-                        fouls: (0, 0),
-                        timeouts: (0, 0),
-                        team_state: [home_state, away_state],
-                        //720 = 12 minutes
-                        time: Duration::from_secs(720),
-                    },
-                    events: Vec::new(),
-                    sim: false,
-                };
+                let game = Game::with_rosters(
+                    (teams[0].clone(), teams[1].clone()),
+                    home_players,
+                    away_players,
+                    seed.unwrap_or_else(rng::new_seed),
+                    rules,
+                );
                 Ok(game)
             }
             Err(e) => Err(e),
         }
     }
 
+    pub fn with_rosters(
+        teams: (Team, Team),
+        home_players: ([Player; 5], Vec<Player>),
+        away_players: ([Player; 5], Vec<Player>),
+        seed: u64,
+        rules: RuleSet,
+    ) -> Game {
+        let home_state = TeamState::new(teams.0.get_id(), home_players.0, home_players.1);
+        let away_state = TeamState::new(teams.1.get_id(), away_players.0, away_players.1);
+        Game {
+            id: None,
+            teams,
+            state: GameState {
+                period: 1,
+                phase: PeriodPhase::Pregame,
+                opening_tip: None,
+                arrow: None,
+                shot_clock: Duration::from_secs(rules.shot_clock),
+                possession: None,
+                score: (0, 0),
+                fouls: (0, 0),
+                timeouts: (rules.timeouts, rules.timeouts),
+                run: None,
+                team_state: [home_state, away_state],
+                time: rules.period_time(1),
+                last_pass: None,
+                play: None,
+                frontcourt: false,
+            },
+            events: Vec::new(),
+            queued_events: VecDeque::new(),
+            injuries: Vec::new(),
+            pending_timeout: None,
+            rules,
+            sim: false,
+            seed,
+            rng: rng::seeded(seed),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
     pub fn change_possession(&mut self, new_possession: Option<(Possession, usize)>) {
         let possession_changed = match (&self.state.possession, &new_possession) {
             (None, None) => false,
@@ -112,22 +266,111 @@ impl Game {
             _ => true,
         };
         if possession_changed {
-            self.state
-                .team_state
-                .iter_mut()
-                .enumerate()
-                .for_each(|(_, s)| {
-                    s.active_players.iter_mut().enumerate().for_each(|(_, p)| {
-                        if p.1.current_area.is_front_court() {
-                            p.1.current_area = CourtArea::Backcourt;
-                        } else {
-                            p.1.current_area = CourtArea::Center;
-                        }
-                    })
-                });
-            self.state.shot_clock = Duration::from_secs(24);
+            // The other team attacks the other basket
+            if let (Some(_), Some(_)) = (self.state.possession, new_possession) {
+                for team_state in self.state.team_state.iter_mut() {
+                    for (_, state) in team_state.active_players.iter_mut() {
+                        state.current_area = state.current_area.mirror();
+                    }
+                }
+            }
+            self.state.shot_clock = Duration::from_secs(self.rules.shot_clock);
+            self.state.last_pass = None;
+            self.state.play = None;
+            self.state.frontcourt = false;
         }
         self.state.possession = new_possession;
+        if let (true, Some((team, _))) = (possession_changed, new_possession) {
+            self.assign_matchups(team.other());
+        }
+    }
+
+    // Runs the game and shot clocks during live play
+    pub fn run_clock(&mut self, elapsed: Duration) {
+        self.state.time = self.state.time.saturating_sub(elapsed);
+        self.state.shot_clock = self.state.shot_clock.saturating_sub(elapsed);
+        self.add_time_played(elapsed);
+    }
+
+    // Index of the player who brings the ball up after an inbound, the best
+    // combination of handle and passing on the floor
+    pub fn ball_handler(&self, team: Possession) -> usize {
+        self.state.team_state[team.index()]
+            .active_players
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, (p, _))| (p.attributes().handle + p.attributes().pass, -(*i as i32)))
+            .map(|(i, _)| i)
+            .unwrap_or_default()
+    }
+
+    // Adds to the score and keeps track of scoring runs
+    pub fn add_points(&mut self, team: Possession, points: u16) {
+        if points == 0 {
+            return;
+        }
+        match team {
+            Possession::Home => self.state.score.0 += points,
+            Possession::Away => self.state.score.1 += points,
+        }
+        self.state.run = match self.state.run {
+            Some((run_team, run)) if run_team == team => Some((team, run + points)),
+            _ => Some((team, points)),
+        };
+    }
+
+    pub fn player_stats_mut(&mut self, team: Possession, index: usize) -> Option<&mut PlayerStats> {
+        let team_state = &mut self.state.team_state[team.index()];
+        let player_id = team_state.active_players[index].0.get_id().ok()?;
+        team_state
+            .box_score
+            .iter_mut()
+            .find(|s| s.player_id() == player_id)
+    }
+
+    // Credits game time to everyone on the floor, tiring them by what they
+    // are doing, and lets the bench recover
+    pub fn add_time_played(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f32();
+        for team in [Possession::Home, Possession::Away] {
+            for index in 0..5 {
+                if let Some(stats) = self.player_stats_mut(team, index) {
+                    stats.add_seconds(elapsed.as_secs_f64());
+                }
+            }
+            let team_state = &mut self.state.team_state[team.index()];
+            for (player, state) in team_state.active_players.iter_mut() {
+                state.drain_energy(player.attributes(), seconds);
+            }
+            for player in team_state.bench.0.iter() {
+                let Ok(id) = player.get_id() else { continue };
+                if let Some(energy) = team_state.resting_energy.get_mut(&id) {
+                    let recovery = BENCH_RECOVERY
+                        * (0.5 + player.attributes().conditioning as f32 / 100.0)
+                        * seconds;
+                    *energy = (*energy + recovery).min(MAX_ENERGY);
+                }
+            }
+        }
+    }
+
+    // Breaks in play (timeouts, between periods) give everyone some energy back
+    pub fn recover_energy(&mut self, amount: f32) {
+        for team_state in self.state.team_state.iter_mut() {
+            for (_, state) in team_state.active_players.iter_mut() {
+                state.recover_energy(amount);
+            }
+            for energy in team_state.resting_energy.values_mut() {
+                *energy = (*energy + amount).min(MAX_ENERGY);
+            }
+        }
+    }
+
+    pub fn get_box_score(&self) -> (Vec<PlayerStats>, Vec<PlayerStats>) {
+        (
+            self.state.team_state[0].box_score.clone(),
+            self.state.team_state[1].box_score.clone(),
+        )
     }
 
     pub fn player_has_ball(&self) -> Option<(&Player, &PlayerState)> {
@@ -145,149 +388,320 @@ impl Game {
     }
 
     pub fn handle_player_actions(&mut self) -> Result<GameEvent, String> {
-        let mut message = String::new();
-        let mut event: Option<GameEvent> = None;
+        let mut kind: Option<GameEventKind> = None;
         let mut new_possession: Option<(Possession, usize)> = self.state.possession;
-        let mut points_added: u8 = 0;
-        if let Some((player, player_state)) = self.player_has_ball() {
+        let mut points_added: u16 = 0;
+        // Team whose shot missed and needs a rebound
+        let mut missed_shot: Option<Possession> = None;
+        // (points, made) when the ball handler shot
+        let mut shot: Option<(u8, bool)> = None;
+        let mut fouled = false;
+        // Team and active player index of a defender who took the ball
+        let mut stolen_by: Option<(Possession, usize)> = None;
+        let mut steal: Option<GameEventKind> = None;
+        // The ball handler got tied up by their defender
+        let mut held_ball = false;
+        let has_ball = self.player_has_ball().map(|(p, s)| (p.clone(), *s));
+        if let Some((player, player_state)) = has_ball {
+            let player_id = player.get_id().unwrap_or_default();
+            kind = Some(GameEventKind::Hold {
+                player: player_id,
+                area: player_state.current_area,
+            });
             let buzzer_beater = self.state.shot_clock < Duration::from_millis(500)
                 || self.state.time < Duration::from_millis(500);
-            if buzzer_beater || player_state.is_shot().is_some() {
+            let shooting = buzzer_beater || player_state.is_shot().is_some();
+            let defender_index = self
+                .state
+                .possession
+                .map(|(team, index)| self.defender_of(team, index));
+            let defender = self
+                .state
+                .possession
+                .zip(defender_index)
+                .map(|((team, _), d)| {
+                    self.state.team_state[team.other().index()].active_players[d].clone()
+                });
+            if let Some((defender, defender_state)) = &defender {
+                let foul_chance = foul::foul_chance(
+                    defender_state,
+                    defender.attributes(),
+                    &player_state,
+                    player.attributes(),
+                    shooting,
+                );
+                fouled = self.rng.gen_range(0.0..1.0) < foul_chance;
+            }
+            if shooting {
                 let points: u8 = if buzzer_beater {
                     player_state.current_area.points()
                 } else {
                     player_state.is_shot().expect("No points generated")
                 };
-                let random = thread_rng().gen_range(0.0..1.0);
-                let shot_chance = player_state.calculate_shot_chance(player.attributes());
-                println!("RNG: {}, Shot Chance: {}", random, shot_chance);
-                if shot_chance > random {
-                    //Shot made
-                    message = format!(
-                        "{} {} {:?} from {:?} and makes it!",
-                        player.first_name,
-                        player.last_name,
-                        player_state.action,
-                        player_state.current_area
-                    );
-
-                    points_added = points;
-                } else {
-                    message = format!(
-                        "{} {} {:?} from {:?} and misses it!",
-                        player.first_name,
-                        player.last_name,
-                        player_state.action,
-                        player_state.current_area
+                let random = self.rng.gen_range(0.0..1.0);
+                let mut shot_chance = player_state.calculate_shot_chance(player.attributes());
+                if points == 3 {
+                    shot_chance *= self.rules.three_point_factor();
+                }
+                let mut blocked = false;
+                if fouled {
+                    shot_chance *= foul::FOULED_SHOT_FACTOR;
+                } else if let Some((defender, defender_state)) = &defender {
+                    if let Some((team, _)) = self.state.possession {
+                        shot_chance *= self.scheme_shot_factor(team, player_state.current_area);
+                    }
+                    shot_chance *= defense::contest_factor(
+                        defender_state,
+                        defender.attributes(),
+                        &player_state,
                     );
+                    let block_chance =
+                        defense::block_chance(defender, defender_state, &player, &player_state);
+                    blocked = self.rng.gen_range(0.0..1.0) < block_chance;
+                }
+                let made = !blocked && shot_chance > random;
+                if made {
+                    points_added = points as u16;
                 }
-                let mut rng = thread_rng();
-                let player_index = rng.gen_range(0..5);
-                match self.state.possession {
-                    Some((Possession::Home, _)) => {
-                        new_possession = Some((Possession::Away, player_index));
+                shot = Some((points, made));
+                kind = Some(GameEventKind::ShotAttempt {
+                    shooter: player_id,
+                    action: player_state.action,
+                    area: player_state.current_area,
+                    points,
+                    made,
+                });
+                if let Some((team, index)) = self.state.possession {
+                    // A miss on a shooting foul is not a field goal attempt
+                    if made || !fouled {
+                        if let Some(stats) = self.player_stats_mut(team, index) {
+                            stats.record_field_goal(points, made);
+                        }
+                    }
+                    match self.state.last_pass {
+                        Some(passer) if made && passer != index => {
+                            if let Some(stats) = self.player_stats_mut(team, passer) {
+                                stats.record_assist();
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.state.last_pass = None;
+                    if let (true, Some(blocker)) = (blocked, defender_index) {
+                        if let Some(stats) = self.player_stats_mut(team.other(), blocker) {
+                            stats.record_block();
+                        }
+                        let block = GameEventKind::Block {
+                            player: defender
+                                .as_ref()
+                                .and_then(|(p, _)| p.get_id().ok())
+                                .unwrap_or_default(),
+                            shooter: player_id,
+                        };
+                        let block = GameEvent::new(block, self);
+                        self.queued_events.push_back(block);
                     }
-                    Some((Possession::Away, _)) => {
-                        new_possession = Some((Possession::Home, player_index));
+                    if made {
+                        new_possession = Some((team.other(), self.ball_handler(team.other())));
+                    } else {
+                        missed_shot = Some(team);
                     }
-                    None => {}
                 }
             }
-            if player_state.action == PlayerAction::Pass {
-                let mut rng = thread_rng();
-                let mut random_index = rng.gen_range(0..5);
-                match self.state.possession {
-                    Some((Possession::Home, index)) => {
-                        while index == random_index {
-                            random_index = rng.gen_range(0..5);
+            if player_state.action == PlayerAction::Pass && !fouled {
+                if let Some((team, index)) = self.state.possession {
+                    let target = pass::pass_target(self, team, index).unwrap_or(index);
+                    let outcome = pass::throw_pass(self, team, index, target);
+                    match outcome {
+                        PassOutcome::Stolen(defender) => stolen_by = Some((team.other(), defender)),
+                        PassOutcome::BadPass | PassOutcome::Backcourt => {
+                            if let Some(stats) = self.player_stats_mut(team, index) {
+                                stats.record_turnover();
+                            }
+                            let reason = match outcome {
+                                PassOutcome::Backcourt => TurnoverReason::Backcourt,
+                                _ => TurnoverReason::BadPass,
+                            };
+                            kind = Some(GameEventKind::Turnover {
+                                player: player_id,
+                                reason,
+                            });
+                            let inbounder = self.ball_handler(team.other());
+                            new_possession = Some((team.other(), inbounder));
                         }
-                        new_possession = Some((Possession::Home, random_index));
-                        let has_ball =
-                            self.state.team_state[0].active_players[random_index].clone();
-                        message = format!(
-                            "{} {} passes to {} {} from {:?} to {:?}",
-                            player.first_name,
-                            player.last_name,
-                            has_ball.0.first_name,
-                            has_ball.0.last_name,
-                            player_state.current_area,
-                            has_ball.1.current_area,
-                        )
-                    }
-                    Some((Possession::Away, index)) => {
-                        while index == random_index {
-                            random_index = rng.gen_range(0..5);
+                        PassOutcome::Complete => {
+                            new_possession = Some((team, target));
+                            self.state.last_pass = Some(index);
+                            let has_ball =
+                                &self.state.team_state[team.index()].active_players[target];
+                            kind = Some(GameEventKind::Pass {
+                                from: player_id,
+                                to: has_ball.0.get_id().unwrap_or_default(),
+                                from_area: player_state.current_area,
+                                to_area: has_ball.1.current_area,
+                            });
                         }
-                        new_possession = Some((Possession::Away, random_index));
-                        let has_ball =
-                            self.state.team_state[1].active_players[random_index].clone();
-                        message = format!(
-                            "{} {} passes to {} {} from {:?} to {:?}",
-                            player.first_name,
-                            player.last_name,
-                            has_ball.0.first_name,
-                            has_ball.0.last_name,
-                            player_state.current_area,
-                            has_ball.1.current_area,
-                        )
                     }
-                    None => {}
                 }
             }
-            if player_state.action == PlayerAction::Drive {
-                message = format!(
-                    "{} {} drives to {:?}",
-                    player.first_name, player.last_name, player_state.current_area,
-                );
-            }
-            match self.state.possession {
-                Some((Possession::Home, _)) => {
-                    //Convert time to mm:ss:ms
-                    event = Some(GameEvent::new(
-                        message,
-                        self.get_time(),
-                        self.state.period,
-                        Some(Possession::Home),
-                    ));
+            if player_state.action == PlayerAction::Drive && !fouled {
+                if let (Some((team, _)), Some((defender, defender_state)), Some(defender_index)) =
+                    (self.state.possession, &defender, defender_index)
+                {
+                    let steal_chance = defense::steal_chance(
+                        defender_state,
+                        defender.attributes(),
+                        player.attributes().handle,
+                    );
+                    let random = self.rng.gen_range(0.0..1.0);
+                    if random < steal_chance {
+                        stolen_by = Some((team.other(), defender_index));
+                    } else if random < steal_chance * (1.0 + held_ball::TIE_UP_SHARE) {
+                        held_ball = true;
+                    }
                 }
-                Some((Possession::Away, _)) => {
-                    event = Some(GameEvent::new(
-                        message,
-                        self.get_time(),
-                        self.state.period,
-                        Some(Possession::Away),
-                    ));
+                if stolen_by.is_none() {
+                    kind = Some(GameEventKind::Drive {
+                        player: player_id,
+                        area: player_state.current_area,
+                    });
                 }
-
-                None => {
-                    event = Some(GameEvent::new(
-                        message,
-                        self.get_time(),
-                        self.state.period,
-                        None,
-                    ));
+            }
+            // The assist only counts if the receiver scores without sitting on the ball
+            if player_state.action == PlayerAction::Idle && !shooting {
+                self.state.last_pass = None;
+            }
+            if let (Some((team, index)), Some((stealer_team, stealer))) =
+                (self.state.possession, stolen_by)
+            {
+                if let Some(stats) = self.player_stats_mut(team, index) {
+                    stats.record_turnover();
+                }
+                if let Some(stats) = self.player_stats_mut(stealer_team, stealer) {
+                    stats.record_steal();
                 }
+                steal = Some(GameEventKind::Steal {
+                    player: self.state.team_state[stealer_team.index()].active_players[stealer]
+                        .0
+                        .get_id()
+                        .unwrap_or_default(),
+                    from: player_id,
+                });
+                kind = Some(GameEventKind::Turnover {
+                    player: player_id,
+                    reason: TurnoverReason::Steal,
+                });
+                new_possession = stolen_by;
             }
         }
+        let event = kind.map(|kind| GameEvent::new(kind, self));
 
-        match self.state.possession {
-            Some((Possession::Home, _)) => {
-                self.state.score.0 += points_added;
+        if let Some((team, _)) = self.state.possession {
+            self.add_points(team, points_added);
+        }
+        if fouled {
+            if let Some((team, index)) = self.state.possession {
+                foul::resolve_foul(self, team, index, shot)?;
             }
-            Some((Possession::Away, _)) => {
-                self.state.score.1 += points_added;
+            injury::check_injuries(self);
+            let _ = self.update_player_states();
+            // An and-one shows the basket first, otherwise the foul replaces the play
+            return match shot {
+                Some((_, true)) => event.ok_or("No event generated".to_string()),
+                _ => self
+                    .queued_events
+                    .pop_front()
+                    .ok_or("No event generated".to_string()),
+            };
+        }
+        match (missed_shot, self.state.possession) {
+            (Some(team), _) => rebound::rebound_miss(self, team)?,
+            (None, Some((team, index))) if held_ball => {
+                held_ball::resolve_held_ball(self, team, index)?
             }
-            None => {}
+            _ => self.change_possession(new_possession),
         }
-        self.change_possession(new_possession);
+        // Queued after the turnover so it carries the new possession
+        if let Some(steal) = steal {
+            let steal = GameEvent::new(steal, self);
+            self.queued_events.push_back(steal);
+        }
+        injury::check_injuries(self);
         let _ = self.update_player_states();
 
-        if event.is_none() {
-            return Err("No event generated".to_string());
-        } else {
-            Ok(event.unwrap())
+        event.ok_or("No event generated".to_string())
+    }
+
+    // Charges the ball handler with a shot clock violation
+    pub fn shot_clock_turnover(&mut self) -> Option<GameEvent> {
+        let (team, index) = self.state.possession?;
+        if let Some(stats) = self.player_stats_mut(team, index) {
+            stats.record_turnover();
         }
+        let player = self.state.team_state[team.index()].active_players[index]
+            .0
+            .get_id()
+            .unwrap_or_default();
+        Some(GameEvent::new(
+            GameEventKind::Turnover {
+                player,
+                reason: TurnoverReason::ShotClock,
+            },
+            self,
+        ))
     }
+
+    // Checked whenever the ball handler moves. Once the ball has been past half
+    // court, taking it back over is a turnover. Queues it and gives the other
+    // team the ball, true when it happened.
+    fn backcourt_violation(&mut self) -> bool {
+        let Some((team, index)) = self.state.possession else {
+            return false;
+        };
+        let (player, state) = &self.state.team_state[team.index()].active_players[index];
+        if state.current_area.is_front_court() {
+            self.state.frontcourt = true;
+            return false;
+        }
+        if !self.state.frontcourt {
+            return false;
+        }
+        let player = player.get_id().unwrap_or_default();
+        if let Some(stats) = self.player_stats_mut(team, index) {
+            stats.record_turnover();
+        }
+        let turnover = GameEvent::new(
+            GameEventKind::Turnover {
+                player,
+                reason: TurnoverReason::Backcourt,
+            },
+            self,
+        );
+        self.queued_events.push_back(turnover);
+        let inbounder = self.ball_handler(team.other());
+        self.change_possession(Some((team.other(), inbounder)));
+        true
+    }
+
+    pub fn find_player(&self, player_id: i64) -> Option<&Player> {
+        self.state.team_state.iter().find_map(|team_state| {
+            team_state
+                .active_players
+                .iter()
+                .map(|(p, _)| p)
+                .chain(team_state.bench.0.iter())
+                .chain(team_state.bench.1.iter())
+                .find(|p| p.get_id() == Ok(player_id))
+        })
+    }
+
+    pub fn player_name(&self, player_id: i64) -> String {
+        match self.find_player(player_id) {
+            Some(player) => format!("{} {}", player.first_name, player.last_name),
+            None => "Unknown player".to_string(),
+        }
+    }
+
     pub fn get_time(&self) -> String {
         let minutes = self.state.time.as_secs() / 60;
         let seconds = self.state.time.as_secs() % 60;
@@ -295,9 +709,13 @@ impl Game {
         let time = format!("{:02}:{:02}:{:03}", minutes, seconds, milliseconds);
         time
     }
-    pub fn get_score(&self) -> (u8, u8) {
+    pub fn get_score(&self) -> (u16, u16) {
         (self.state.score.0, self.state.score.1)
     }
+    // Team with the ball, whose basket the areas in get_player_states face
+    pub fn get_possession(&self) -> Option<Possession> {
+        self.state.possession.map(|(team, _)| team)
+    }
     pub fn get_player_states(&self) -> Vec<(Player, PlayerState)> {
         let home_players = self.state.team_state[0].active_players.clone();
         let away_players = self.state.team_state[1].active_players.clone();
@@ -307,76 +725,103 @@ impl Game {
             .collect();
         all_players
     }
+    // Moves everyone for the next tick. The offense runs its play, the defense
+    // reacts according to its scheme and then the ball handler makes their move.
     pub fn update_player_states(&mut self) -> Result<(), String> {
-        let game_state = self.state.clone();
-        self.state
-            .team_state
+        let Some((offense, ball_handler)) = self.state.possession else {
+            return Ok(());
+        };
+        let assignments = self.play_assignments();
+        for (j, (player, state)) in self.state.team_state[offense.index()]
+            .active_players
             .iter_mut()
             .enumerate()
-            .for_each(|(i, s)| {
-                s.active_players.iter_mut().enumerate().for_each(|(j, p)| {
-                    match self.state.possession {
-                        Some((Possession::Home, index)) => {
-                            if i == 0 {
-                                if j == index {
-                                    let _ = p.1.generate_next_player_state(
-                                        p.0.attributes(),
-                                        (true, true),
-                                        (false, None),
-                                    );
-                                } else {
-                                    let _ = p.1.generate_next_player_state(
-                                        p.0.attributes(),
-                                        (true, false),
-                                        (false, None),
-                                    );
-                                }
-                            } else {
-                                let _ = p.1.generate_next_player_state(
-                                    p.0.attributes(),
-                                    (false, false),
-                                    (true, Some(&game_state.team_state[0].active_players[j].1)),
-                                );
-                            }
-                        }
-                        Some((Possession::Away, index)) => {
-                            if i == 1 {
-                                if j == index {
-                                    let _ = p.1.generate_next_player_state(
-                                        p.0.attributes(),
-                                        (true, true),
-                                        (false, None),
-                                    );
-                                } else {
-                                    let _ = p.1.generate_next_player_state(
-                                        p.0.attributes(),
-                                        (true, false),
-                                        (false, None),
-                                    );
-                                }
-                            } else {
-                                let _ = p.1.generate_next_player_state(
-                                    p.0.attributes(),
-                                    (false, false),
-                                    (true, Some(&game_state.team_state[1].active_players[j].1)),
-                                );
-                            }
-                        }
-                        None => {
-                            return;
-                        }
-                    }
-                })
-            });
+        {
+            let has_ball = j == ball_handler;
+            match assignments {
+                Some(assignments) => {
+                    state.run_play(has_ball, assignments[j], &mut self.rng);
+                    state.sprint(player.attributes(), assignments[j].0, &mut self.rng);
+                }
+                None => state.generate_next_player_state(
+                    (true, has_ball),
+                    (false, None),
+                    &mut self.rng,
+                )?,
+            }
+        }
+        self.switch_on_screens();
+        let targets = self.defense_targets(offense.other());
+        for (j, (player, state)) in self.state.team_state[offense.other().index()]
+            .active_players
+            .iter_mut()
+            .enumerate()
+        {
+            state.defend(targets[j], &mut self.rng);
+            state.sprint(player.attributes(), targets[j], &mut self.rng);
+        }
+        // Checked before the ball handler decides, so they know whether they
+        // can still pass back, and again after a drive. After a violation the
+        // other team has the ball and starts bringing it up next tick.
+        if self.backcourt_violation() {
+            return Ok(());
+        }
+        self.decide();
+        self.backcourt_violation();
         Ok(())
     }
 
     pub fn generate_next_game_event(&mut self) -> Result<GameEvent, String> {
-        if self.events.len() == 0 {
-            let _ = jump_ball::generate_jump_ball(self);
-            self.state.time = Duration::from_secs(720);
+        if let Some(event) = self.queued_events.pop_front() {
+            self.events.push(event.clone());
+            return Ok(event);
+        }
+        let dead_ball = self.events.last().is_some_and(|e| e.kind.is_dead_ball());
+        // No timeouts between periods or with the clock at zero
+        let clock_running = self.state.phase == PeriodPhase::Live && !self.state.time.is_zero();
+        // The user's timeout waits for a dead ball unless their team has the ball
+        let timeout = match self.pending_timeout {
+            _ if !clock_running => None,
+            Some(team)
+                if dead_ball || matches!(self.state.possession, Some((t, _)) if t == team) =>
+            {
+                self.pending_timeout = None;
+                Some(team)
+            }
+            _ if dead_ball => timeout::ai_timeout(self),
+            _ => None,
+        };
+        if let Some(event) = timeout.and_then(|team| self.take_timeout(team)) {
+            self.events.push(event.clone());
+            return Ok(event);
+        }
+        if dead_ball {
+            for team in [Possession::Home, Possession::Away] {
+                let substitutions = rotation::check_substitutions(self, team);
+                self.queued_events.extend(substitutions);
+            }
+            if let Some(event) = self.queued_events.pop_front() {
+                self.events.push(event.clone());
+                return Ok(event);
+            }
         }
-        //Print score
+        let event = game_event::GameEvent::generate_next_game_event(self)?;
+        self.events.push(event.clone());
+        Ok(event)
+    }
+
+    // Runs the game to the final buzzer without sleeping or emitting anything
+    pub fn simulate_to_end(&mut self) -> Result<(Vec<GameEvent>, GameState), String> {
+        for _ in 0..MAX_GAME_EVENTS {
+            let event = self.generate_next_game_event()?;
+            if event.is_game_end() {
+                return Ok((self.events.clone(), self.state.clone()));
+            }
+        }
+        Err(format!("Game did not end after {} events", MAX_GAME_EVENTS))
+    }
+
+    pub fn print_state(&self) {
         println!("------------------------------------------------------");
         println!("Home: {}, Away: {}", self.state.score.0, self.state.score.1);
         let total_ms = self.state.time.as_millis();
@@ -399,9 +844,74 @@ impl Game {
                 );
             }
         });
-        let event = game_event::GameEvent::generate_next_game_event(self);
         println!("------------------------------------------------------");
-        //Wait 3 seconds
-        Ok(event.unwrap())
     }
 }
+
+#[cfg(test)]
+pub fn test_game(seed: u64) -> Game {
+    use crate::player::player_attributes::PlayerAttributes;
+
+    let player = |team_id: i64, i: usize| {
+        let rating = 40 + (i as i32 % 5) * 10;
+        Player::new(
+            Some(team_id * 10 + i as i64),
+            format!("Player{}", i),
+            format!("Team{}", team_id),
+            "SF".to_string(),
+            25,
+            6,
+            200,
+            PlayerAttributes {
+                spd: rating,
+                close_shot: rating + 10,
+                mid_shot: rating,
+                deep_shot: rating - 10,
+                ath: rating,
+                ..PlayerAttributes::new()
+            },
+        )
+    };
+    let roster = |team_id: i64| -> ([Player; 5], Vec<Player>) {
+        (
+            std::array::from_fn(|i| player(team_id, i)),
+            (5..8).map(|i| player(team_id, i)).collect(),
+        )
+    };
+    Game::with_rosters(
+        (
+            Team::new(Some(1), "Home".to_string(), "Home City".to_string()),
+            Team::new(Some(2), "Away".to_string(), "Away City".to_string()),
+        ),
+        roster(1),
+        roster(2),
+        seed,
+        RuleSet::nba(),
+    )
+}
+
+#[test]
+fn test_same_seed_replays_game() {
+    let mut first = test_game(42);
+    let mut second = test_game(42);
+    for _ in 0..300 {
+        let a = first.generate_next_game_event().unwrap();
+        let b = second.generate_next_game_event().unwrap();
+        assert_eq!(a, b);
+    }
+    assert_eq!(first.get_score(), second.get_score());
+}
+
+#[test]
+fn test_simulate_to_end() {
+    let mut game = test_game(7);
+    let (events, state) = game.simulate_to_end().unwrap();
+    assert!(events.last().unwrap().is_game_end());
+    assert!(state.period >= 4);
+    assert_ne!(state.score.0, state.score.1);
+
+    let (home, away) = game.get_box_score();
+    let points = |stats: &Vec<PlayerStats>| stats.iter().map(|s| s.points()).sum::<f64>();
+    assert_eq!(points(&home), state.score.0 as f64);
+    assert_eq!(points(&away), state.score.1 as f64);
+}
@@ -0,0 +1,170 @@
+use crate::game::court::CourtArea;
+use crate::game::event::game_event::{GameEvent, GameEventKind};
+use crate::game::event::jump_ball;
+use crate::game::{Game, Possession, HALFTIME_RECOVERY, PERIOD_BREAK_RECOVERY};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Where the game is between periods. Play only runs while Live and the game
+// clock is above zero.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PeriodPhase {
+    // Before the opening tip
+    #[default]
+    Pregame,
+    Live,
+    // Between the end of a period and the start of the next
+    Break,
+    Final,
+}
+
+impl Game {
+    pub fn is_overtime(&self) -> bool {
+        self.state.period > self.rules.periods
+    }
+
+    // "Period 2" or "Overtime 1" for the play-by-play
+    pub fn period_name(&self, period: u8) -> String {
+        if period > self.rules.periods {
+            format!("Overtime {}", period - self.rules.periods)
+        } else {
+            format!("Period {}", period)
+        }
+    }
+
+    // Moves the game on once the clock has run out, or starts it
+    pub fn next_period_event(&mut self) -> Result<GameEvent, String> {
+        match self.state.phase {
+            PeriodPhase::Pregame | PeriodPhase::Break => self.start_period(),
+            PeriodPhase::Live => Ok(self.end_period()),
+            PeriodPhase::Final => Ok(GameEvent::new(GameEventKind::GameEnd, self)),
+        }
+    }
+
+    // Ends the current period. The game is over once a period at or after the
+    // end of regulation ends without a tie.
+    fn end_period(&mut self) -> GameEvent {
+        let period = self.state.period;
+        let event = GameEvent::new(GameEventKind::PeriodEnd { period }, self);
+        let tied = self.state.score.0 == self.state.score.1;
+        if period >= self.rules.periods && !tied {
+            self.state.phase = PeriodPhase::Final;
+            let game_end = GameEvent::new(GameEventKind::GameEnd, self);
+            self.queued_events.push_back(game_end);
+        } else {
+            self.state.phase = PeriodPhase::Break;
+            self.recover_energy(if period == self.rules.halftime_period() {
+                HALFTIME_RECOVERY
+            } else {
+                PERIOD_BREAK_RECOVERY
+            });
+        }
+        self.state.time = Duration::ZERO;
+        // Everyone heads back to center court for the restart
+        self.change_possession(None);
+        for team_state in self.state.team_state.iter_mut() {
+            for (_, state) in team_state.active_players.iter_mut() {
+                state.current_area = CourtArea::Center;
+            }
+        }
+        event
+    }
+
+    // Resets the clocks and fouls and gives someone the ball. Overtime and the
+    // first period start with a jump ball.
+    fn start_period(&mut self) -> Result<GameEvent, String> {
+        if self.state.phase == PeriodPhase::Break {
+            self.state.period += 1;
+            // Team fouls only count towards the bonus within a period
+            self.state.fouls = (0, 0);
+            self.start_period_timeouts();
+        }
+        self.state.phase = PeriodPhase::Live;
+        self.state.time = self.rules.period_time(self.state.period);
+        self.state.shot_clock = Duration::from_secs(self.rules.shot_clock);
+        let jump_ball = match self.start_possession() {
+            Some(team) => {
+                let handler = self.ball_handler(team);
+                self.change_possession(Some((team, handler)));
+                self.update_player_states()?;
+                None
+            }
+            None => Some(jump_ball::generate_jump_ball(self)?),
+        };
+        let event = GameEvent::new(
+            GameEventKind::PeriodStart {
+                period: self.state.period,
+            },
+            self,
+        );
+        self.queued_events.extend(jump_ball);
+        Ok(event)
+    }
+
+    // Team with the next alternating possession, the arrow flips once used
+    pub fn use_arrow(&mut self) -> Option<Possession> {
+        let team = self.state.arrow?;
+        self.state.arrow = Some(team.other());
+        Some(team)
+    }
+
+    // Team that inbounds to start the period, None for a jump ball. With the
+    // possession arrow every period after the opening tip goes by the arrow.
+    // Otherwise overtime starts with a jump ball and the team that lost the
+    // opening tip starts the following periods except the last of regulation.
+    fn start_possession(&mut self) -> Option<Possession> {
+        if self.state.period == 1 {
+            return None;
+        }
+        if self.rules.possession_arrow {
+            return self.use_arrow();
+        }
+        if self.is_overtime() {
+            return None;
+        }
+        let winner = self.state.opening_tip?;
+        if self.state.period == self.rules.periods && self.rules.periods > 2 {
+            Some(winner)
+        } else {
+            Some(winner.other())
+        }
+    }
+}
+
+#[test]
+fn test_overtime_after_tie() {
+    let mut game = crate::game::test_game(7);
+    let periods = game.rules.periods;
+    game.state.phase = PeriodPhase::Live;
+    game.state.period = periods;
+    game.state.time = Duration::ZERO;
+    game.state.score = (100, 100);
+
+    let end = game.next_period_event().unwrap();
+    assert_eq!(end.kind, GameEventKind::PeriodEnd { period: periods });
+    assert_eq!(end.period, periods);
+    assert_eq!(game.state.phase, PeriodPhase::Break);
+
+    for overtime in 1..=3 {
+        let start = game.next_period_event().unwrap();
+        assert_eq!(start.period, periods + overtime);
+        assert_eq!(game.state.time, game.rules.period_time(periods + overtime));
+        let jump_ball = game.queued_events.pop_front().unwrap();
+        assert!(matches!(jump_ball.kind, GameEventKind::JumpBall { .. }));
+        assert_eq!(jump_ball.period, periods + overtime);
+
+        game.state.time = Duration::ZERO;
+        let end = game.next_period_event().unwrap();
+        assert_eq!(end.period, periods + overtime);
+    }
+    assert_eq!(game.state.phase, PeriodPhase::Break);
+
+    game.next_period_event().unwrap();
+    game.queued_events.clear();
+    game.state.time = Duration::ZERO;
+    game.state.score = (105, 102);
+    game.next_period_event().unwrap();
+    assert_eq!(game.state.phase, PeriodPhase::Final);
+    assert!(game.queued_events.back().unwrap().is_game_end());
+    assert_eq!(game.queued_events.back().unwrap().period, periods + 4);
+}
@@ -0,0 +1,431 @@
+use crate::game::court::CourtArea;
+use crate::game::{Game, Possession};
+use crate::player::player_attributes::PlayerAttributes;
+use crate::player::player_state::PlayerAction;
+use rand::distributions::{Distribution, WeightedIndex};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+// Ticks a play runs before the offense calls the next one. The first
+// SETUP_TICKS get everyone into position.
+const PLAY_TICKS: u8 = 5;
+const SETUP_TICKS: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlayType {
+    PickAndRoll,
+    Isolation,
+    PostUp,
+    Horns,
+    Motion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlayRole {
+    // The player the play is run for
+    Primary,
+    Screener,
+    Cutter,
+    Spacer,
+}
+
+// How often a team runs each play relative to the others
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayTendencies {
+    pub pick_and_roll: i32,
+    pub isolation: i32,
+    pub post_up: i32,
+    pub horns: i32,
+    pub motion: i32,
+}
+
+impl Default for PlayTendencies {
+    fn default() -> Self {
+        PlayTendencies {
+            pick_and_roll: 30,
+            isolation: 15,
+            post_up: 15,
+            horns: 15,
+            motion: 25,
+        }
+    }
+}
+
+impl PlayTendencies {
+    pub fn weight(&self, play: PlayType) -> i32 {
+        match play {
+            PlayType::PickAndRoll => self.pick_and_roll,
+            PlayType::Isolation => self.isolation,
+            PlayType::PostUp => self.post_up,
+            PlayType::Horns => self.horns,
+            PlayType::Motion => self.motion,
+        }
+    }
+
+    // Weights can't be negative and the team has to run something
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.pick_and_roll,
+            self.isolation,
+            self.post_up,
+            self.horns,
+            self.motion,
+        ];
+        if weights.iter().any(|&w| w < 0) {
+            return Err("Play tendencies can't be negative".to_string());
+        }
+        if weights.iter().all(|&w| w == 0) {
+            return Err("A team needs at least one play it runs".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn write_to_db(&self, team_id: i64, conn: &Connection) -> rusqlite::Result<()> {
+        self.validate()
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO team_tendencies (team_id, pick_and_roll, isolation, post_up, horns, motion)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                team_id,
+                self.pick_and_roll,
+                self.isolation,
+                self.post_up,
+                self.horns,
+                self.motion,
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Reads TENDENCY_COLUMNS starting at column `start`. Teams without stored
+    // tendencies, or with invalid ones, get the defaults.
+    pub fn from_row(row: &Row, start: usize) -> Result<PlayTendencies, rusqlite::Error> {
+        let pick_and_roll: Option<i32> = row.get(start)?;
+        let tendencies = match pick_and_roll {
+            Some(pick_and_roll) => PlayTendencies {
+                pick_and_roll,
+                isolation: row.get(start + 1)?,
+                post_up: row.get(start + 2)?,
+                horns: row.get(start + 3)?,
+                motion: row.get(start + 4)?,
+            },
+            None => PlayTendencies::default(),
+        };
+        match tendencies.validate() {
+            Ok(()) => Ok(tendencies),
+            Err(_) => Ok(PlayTendencies::default()),
+        }
+    }
+}
+
+// Column order read by PlayTendencies::from_row, the query has to left join
+// team_tendencies
+pub const TENDENCY_COLUMNS: &str = "team_tendencies.pick_and_roll, team_tendencies.isolation,
+    team_tendencies.post_up, team_tendencies.horns, team_tendencies.motion";
+
+// The set the offense is running and where it is in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivePlay {
+    pub play: PlayType,
+    // By active player index
+    pub roles: [PlayRole; 5],
+    pub ticks: u8,
+    // Screener the defense already switched on, a screen is only switched once
+    #[serde(default)]
+    pub switched: Option<usize>,
+}
+
+// How well a player fits being the primary of `play`, 0 to 100
+fn primary_score(play: PlayType, a: &PlayerAttributes) -> i32 {
+    match play {
+        PlayType::PickAndRoll => (a.handle + a.pass) / 2,
+        PlayType::Isolation => (a.handle + a.spd + a.mid_shot.max(a.deep_shot)) / 3,
+        PlayType::PostUp => (a.close_shot + a.strength + a.shot_in_traffic) / 3,
+        PlayType::Horns | PlayType::Motion => (a.pass + a.intelligence) / 2,
+    }
+}
+
+// Bigs who set solid screens and finish on the roll
+fn screener_score(a: &PlayerAttributes) -> i32 {
+    (a.strength + a.close_shot) / 2
+}
+
+fn screeners(play: PlayType) -> usize {
+    match play {
+        PlayType::PickAndRoll => 1,
+        PlayType::Horns => 2,
+        _ => 0,
+    }
+}
+
+fn cutters(play: PlayType) -> usize {
+    match play {
+        PlayType::Motion => 2,
+        PlayType::PostUp => 1,
+        _ => 0,
+    }
+}
+
+// Hands out the roles of `play` to the five players. Motion is run through
+// whoever has the ball, other plays through the best fit. Screeners are the
+// best bigs left, cutters the worst shooters left.
+pub fn assign_roles(
+    play: PlayType,
+    players: &[&PlayerAttributes; 5],
+    ball_handler: usize,
+) -> [PlayRole; 5] {
+    let mut roles = [PlayRole::Spacer; 5];
+    let primary = if play == PlayType::Motion {
+        ball_handler
+    } else {
+        (0..5)
+            .max_by_key(|&i| (primary_score(play, players[i]), i == ball_handler))
+            .unwrap_or(ball_handler)
+    };
+    roles[primary] = PlayRole::Primary;
+
+    let mut others: Vec<usize> = (0..5).filter(|&i| i != primary).collect();
+    others.sort_by_key(|&i| -screener_score(players[i]));
+    for &i in others.iter().take(screeners(play)) {
+        roles[i] = PlayRole::Screener;
+    }
+    let mut spacers: Vec<usize> = (0..5).filter(|&i| roles[i] == PlayRole::Spacer).collect();
+    spacers.sort_by_key(|&i| players[i].mid_shot.max(players[i].deep_shot));
+    for &i in spacers.iter().take(cutters(play)) {
+        roles[i] = PlayRole::Cutter;
+    }
+    roles
+}
+
+// Where the player in `role` heads and what they do. `slot` tells players
+// with the same role apart, `setup` is true while the play gets into position.
+fn assignment(
+    play: PlayType,
+    role: PlayRole,
+    slot: usize,
+    setup: bool,
+) -> (CourtArea, PlayerAction) {
+    const SPOTS: [CourtArea; 5] = [
+        CourtArea::ThreePointLineCornerLeft,
+        CourtArea::ThreePointLineCornerRight,
+        CourtArea::ThreePointLineWingRight,
+        CourtArea::ThreePointLineWingLeft,
+        CourtArea::ThreePointLineCenter,
+    ];
+    match (play, role) {
+        (PlayType::PickAndRoll, PlayRole::Primary) if setup => {
+            (CourtArea::ThreePointLineCenter, PlayerAction::SpotUp)
+        }
+        (PlayType::PickAndRoll, PlayRole::Primary) => {
+            (CourtArea::FreeThrowLine, PlayerAction::SpotUp)
+        }
+        (PlayType::PickAndRoll, PlayRole::Screener) if setup => {
+            (CourtArea::ThreePointLineCenter, PlayerAction::BallScreen)
+        }
+        (PlayType::PickAndRoll, PlayRole::Screener) => {
+            (CourtArea::RestrictedAreaMiddle, PlayerAction::Cut)
+        }
+        (PlayType::Isolation, PlayRole::Primary) if setup => {
+            (CourtArea::ThreePointLineWingLeft, PlayerAction::SpotUp)
+        }
+        (PlayType::Isolation, PlayRole::Primary) => (CourtArea::ElbowLeft, PlayerAction::SpotUp),
+        (PlayType::PostUp, PlayRole::Primary) => (CourtArea::LowPostLeft, PlayerAction::SpotUp),
+        (PlayType::PostUp, PlayRole::Cutter) if setup => {
+            (CourtArea::ThreePointLineWingRight, PlayerAction::SpotUp)
+        }
+        (PlayType::PostUp, PlayRole::Cutter) => (CourtArea::RestrictedAreaRight, PlayerAction::Cut),
+        (PlayType::Horns, PlayRole::Primary) if setup => {
+            (CourtArea::ThreePointLineCenter, PlayerAction::SpotUp)
+        }
+        (PlayType::Horns, PlayRole::Primary) => (CourtArea::FreeThrowLine, PlayerAction::SpotUp),
+        // One big rolls to the rim and the other pops out
+        (PlayType::Horns, PlayRole::Screener) => match (slot, setup) {
+            (0, true) => (CourtArea::ElbowLeft, PlayerAction::BallScreen),
+            (_, true) => (CourtArea::ElbowRight, PlayerAction::OffBallScreen),
+            (0, false) => (CourtArea::RestrictedAreaLeft, PlayerAction::Cut),
+            (_, false) => (CourtArea::ThreePointLineWingRight, PlayerAction::SpotUp),
+        },
+        (PlayType::Motion, PlayRole::Primary) => {
+            (CourtArea::ThreePointLineCenter, PlayerAction::SpotUp)
+        }
+        (PlayType::Motion, PlayRole::Cutter) => match (slot, setup) {
+            (0, true) => (
+                CourtArea::ThreePointLineWingLeft,
+                PlayerAction::OffBallScreen,
+            ),
+            (_, true) => (
+                CourtArea::ThreePointLineWingRight,
+                PlayerAction::OffBallScreen,
+            ),
+            (0, false) => (CourtArea::RestrictedAreaLeft, PlayerAction::Cut),
+            (_, false) => (CourtArea::RestrictedAreaRight, PlayerAction::Cut),
+        },
+        // Roles a play doesn't use crash the glass
+        (_, PlayRole::Screener) | (_, PlayRole::Cutter) => {
+            (CourtArea::RestrictedAreaMiddle, PlayerAction::Rebound)
+        }
+        (_, PlayRole::Spacer) => (SPOTS[slot % SPOTS.len()], PlayerAction::SpotUp),
+    }
+}
+
+impl Game {
+    // Picks the next play for `team` weighted by the team's tendencies and how
+    // well the five on the floor fit each play. Motion when nothing has any
+    // weight.
+    pub fn call_play(&mut self, team: Possession, ball_handler: usize) -> ActivePlay {
+        let plays = [
+            PlayType::PickAndRoll,
+            PlayType::Isolation,
+            PlayType::PostUp,
+            PlayType::Horns,
+            PlayType::Motion,
+        ];
+        let tendencies = match team {
+            Possession::Home => self.teams.0.get_tendencies(),
+            Possession::Away => self.teams.1.get_tendencies(),
+        };
+        let players: [&PlayerAttributes; 5] = std::array::from_fn(|i| {
+            self.state.team_state[team.index()].active_players[i]
+                .0
+                .attributes()
+        });
+        let weights: Vec<i32> = plays
+            .iter()
+            .map(|&play| {
+                let fit = players
+                    .iter()
+                    .map(|a| primary_score(play, a))
+                    .max()
+                    .unwrap_or_default();
+                tendencies.weight(play).max(0) * (50 + fit)
+            })
+            .collect();
+        let play = match WeightedIndex::new(&weights) {
+            Ok(dist) => plays[dist.sample(&mut self.rng)],
+            Err(_) => PlayType::Motion,
+        };
+        let roles = assign_roles(play, &players, ball_handler);
+        ActivePlay {
+            play,
+            roles,
+            ticks: 0,
+            switched: None,
+        }
+    }
+
+    // Target area and action of each player on the team with the ball for this
+    // tick. Calls a new play when the last one has run its course, in transition
+    // everyone runs their lane instead.
+    pub fn play_assignments(&mut self) -> Option<[(CourtArea, PlayerAction); 5]> {
+        let (team, ball_handler) = self.state.possession?;
+        // Plays start once the ball is past half court
+        if self.in_transition() {
+            return Some(self.transition_assignments(team, ball_handler));
+        }
+        let mut active = match self.state.play.take() {
+            Some(play) if play.ticks < PLAY_TICKS => play,
+            _ => self.call_play(team, ball_handler),
+        };
+        let setup = active.ticks < SETUP_TICKS;
+        let mut slots = [0; 4];
+        let assignments = active.roles.map(|role| {
+            let slot = &mut slots[role as usize];
+            *slot += 1;
+            assignment(active.play, role, *slot - 1, setup)
+        });
+        active.ticks += 1;
+        self.state.play = Some(active);
+        Some(assignments)
+    }
+}
+
+#[test]
+fn test_pick_and_roll_roles() {
+    let mut guard = PlayerAttributes::new();
+    guard.handle = 90;
+    guard.pass = 80;
+    let mut big = PlayerAttributes::new();
+    big.strength = 90;
+    big.close_shot = 80;
+    let wing = PlayerAttributes::new();
+    let players = [&wing, &big, &wing, &guard, &wing];
+
+    let roles = assign_roles(PlayType::PickAndRoll, &players, 0);
+    assert_eq!(roles[3], PlayRole::Primary);
+    assert_eq!(roles[1], PlayRole::Screener);
+    assert_eq!(roles.iter().filter(|r| **r == PlayRole::Spacer).count(), 3);
+
+    // Motion goes through whoever has the ball
+    let roles = assign_roles(PlayType::Motion, &players, 2);
+    assert_eq!(roles[2], PlayRole::Primary);
+    assert_eq!(roles.iter().filter(|r| **r == PlayRole::Cutter).count(), 2);
+}
+
+#[test]
+fn test_tendencies_pick_plays() {
+    let mut game = crate::game::test_game(4);
+    game.teams.0.set_tendencies(PlayTendencies {
+        pick_and_roll: 0,
+        isolation: 100,
+        post_up: 0,
+        horns: 0,
+        motion: 0,
+    });
+    game.state.possession = Some((Possession::Home, 0));
+    game.state.team_state[0].active_players[0].1.current_area = CourtArea::ThreePointLineCenter;
+    for _ in 0..20 {
+        assert_eq!(
+            game.call_play(Possession::Home, 0).play,
+            PlayType::Isolation
+        );
+    }
+    // A play sticks for several ticks before the next is called
+    game.play_assignments();
+    let roles = game.state.play.as_ref().unwrap().roles;
+    for _ in 1..PLAY_TICKS {
+        game.play_assignments();
+        assert_eq!(game.state.play.as_ref().unwrap().roles, roles);
+    }
+}
+
+#[test]
+fn test_invalid_tendencies() {
+    let none = PlayTendencies {
+        pick_and_roll: 0,
+        isolation: 0,
+        post_up: 0,
+        horns: 0,
+        motion: 0,
+    };
+    let negative = PlayTendencies {
+        isolation: -10,
+        ..PlayTendencies::default()
+    };
+    assert!(none.validate().is_err());
+    assert!(negative.validate().is_err());
+
+    // A team that runs nothing falls back to motion instead of panicking
+    let mut game = crate::game::test_game(4);
+    game.teams.0.set_tendencies(none.clone());
+    assert_eq!(game.call_play(Possession::Home, 0).play, PlayType::Motion);
+
+    let mut conn = Connection::open_in_memory().unwrap();
+    crate::util::migrations::migrate(&mut conn).unwrap();
+    assert!(none.write_to_db(1, &conn).is_err());
+    // Bad rows written by hand load as the defaults
+    conn.execute(
+        "INSERT INTO team_tendencies (team_id, pick_and_roll, isolation, post_up, horns, motion)
+        VALUES (1, -5, 0, 0, 0, 0)",
+        [],
+    )
+    .unwrap();
+    let loaded = conn
+        .query_row(
+            &format!("SELECT {} FROM team_tendencies", TENDENCY_COLUMNS),
+            [],
+            |row| PlayTendencies::from_row(row, 0),
+        )
+        .unwrap();
+    assert_eq!(loaded, PlayTendencies::default());
+}
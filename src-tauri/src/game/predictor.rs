@@ -0,0 +1,154 @@
+use crate::game::rules::RuleSet;
+use crate::game::Game;
+use crate::player::Player;
+use crate::team::Team;
+use crate::util::rng;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Width in points of each bucket in the margin histogram
+const MARGIN_BUCKET_SIZE: i32 = 5;
+// Most games one prediction may simulate
+pub const MAX_SIMULATIONS: u32 = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupPrediction {
+    pub home_team_id: Option<i64>,
+    pub away_team_id: Option<i64>,
+    pub simulations: u32,
+    pub seed: u64,
+    pub home_win_probability: f64,
+    pub away_win_probability: f64,
+    // Positive margins favour the home team
+    pub average_margin: f64,
+    pub average_score: (f64, f64),
+    // (lowest margin in bucket, games) sorted by margin
+    pub margin_histogram: Vec<(i32, u32)>,
+    pub overtime_frequency: f64,
+}
+
+// Rosters for a matchup, loaded once and reused for every simulated game
+#[derive(Debug, Clone)]
+pub struct Matchup {
+    teams: (Team, Team),
+    home_players: ([Player; 5], Vec<Player>),
+    away_players: ([Player; 5], Vec<Player>),
+    rules: RuleSet,
+}
+
+impl Matchup {
+    pub fn load(
+        db: &Connection,
+        home_team_id: i64,
+        away_team_id: i64,
+        rules: RuleSet,
+    ) -> Result<Matchup, rusqlite::Error> {
+        let home = Team::get_team(&home_team_id, db)?;
+        let away = Team::get_team(&away_team_id, db)?;
+        let home_players = (home.get_starting_lineup(db)?, home.get_bench(db)?);
+        let away_players = (away.get_starting_lineup(db)?, away.get_bench(db)?);
+        Ok(Matchup {
+            teams: (home, away),
+            home_players,
+            away_players,
+            rules,
+        })
+    }
+
+    pub fn new_game(&self, seed: u64) -> Game {
+        Game::with_rosters(
+            self.teams.clone(),
+            self.home_players.clone(),
+            self.away_players.clone(),
+            seed,
+            self.rules.clone(),
+        )
+    }
+}
+
+impl MatchupPrediction {
+    // `on_game` gets every finished game, e.g. to save it
+    pub fn simulate(
+        matchup: &Matchup,
+        simulations: u32,
+        seed: Option<u64>,
+        mut on_game: impl FnMut(&mut Game) -> Result<(), String>,
+    ) -> Result<MatchupPrediction, String> {
+        if simulations == 0 {
+            return Err("At least one simulation is required".to_string());
+        }
+        if simulations > MAX_SIMULATIONS {
+            return Err(format!(
+                "At most {} simulations are allowed",
+                MAX_SIMULATIONS
+            ));
+        }
+
+        let seed = seed.unwrap_or_else(rng::new_seed);
+        let mut home_wins = 0;
+        let mut away_wins = 0;
+        let mut overtimes = 0;
+        let mut total_margin: i64 = 0;
+        let mut total_score: (u64, u64) = (0, 0);
+        let mut histogram: BTreeMap<i32, u32> = BTreeMap::new();
+        for i in 0..simulations {
+            let mut game = matchup.new_game(seed.wrapping_add(i as u64));
+            let (_, state) = game.simulate_to_end()?;
+            on_game(&mut game)?;
+            let margin = state.score.0 as i32 - state.score.1 as i32;
+            if margin > 0 {
+                home_wins += 1;
+            } else if margin < 0 {
+                away_wins += 1;
+            }
+            if state.period > game.rules.periods {
+                overtimes += 1;
+            }
+            total_margin += margin as i64;
+            total_score.0 += state.score.0 as u64;
+            total_score.1 += state.score.1 as u64;
+            let bucket = margin.div_euclid(MARGIN_BUCKET_SIZE) * MARGIN_BUCKET_SIZE;
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+
+        let games = simulations as f64;
+        Ok(MatchupPrediction {
+            home_team_id: matchup.teams.0.get_id(),
+            away_team_id: matchup.teams.1.get_id(),
+            simulations,
+            seed,
+            home_win_probability: home_wins as f64 / games,
+            away_win_probability: away_wins as f64 / games,
+            average_margin: total_margin as f64 / games,
+            average_score: (total_score.0 as f64 / games, total_score.1 as f64 / games),
+            margin_histogram: histogram.into_iter().collect(),
+            overtime_frequency: overtimes as f64 / games,
+        })
+    }
+}
+
+#[test]
+fn test_prediction_is_reproducible() {
+    let game = crate::game::test_game(0);
+    let roster = |i: usize| {
+        let team_state = &game.state.team_state[i];
+        (
+            team_state.active_players.clone().map(|(player, _)| player),
+            team_state.bench.0.clone(),
+        )
+    };
+    let matchup = Matchup {
+        teams: game.teams.clone(),
+        home_players: roster(0),
+        away_players: roster(1),
+        rules: game.rules.clone(),
+    };
+    let first = MatchupPrediction::simulate(&matchup, 2, Some(11), |_| Ok(())).unwrap();
+    let second = MatchupPrediction::simulate(&matchup, 2, Some(11), |_| Ok(())).unwrap();
+    assert!(first.home_win_probability + first.away_win_probability <= 1.0);
+    assert_eq!(first.home_win_probability, second.home_win_probability);
+    assert_eq!(first.average_score, second.average_score);
+    assert_eq!(first.margin_histogram, second.margin_histogram);
+    assert!(MatchupPrediction::simulate(&matchup, MAX_SIMULATIONS + 1, None, |_| Ok(())).is_err());
+}
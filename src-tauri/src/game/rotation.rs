@@ -0,0 +1,213 @@
+use crate::game::event::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession, TeamState};
+use crate::player::player_state::MAX_ENERGY;
+use crate::player::Player;
+use serde::{Deserialize, Serialize};
+
+const STARTER_MINUTES: f64 = 32.0;
+// Minutes for the first bench players in order, the rest only play when needed
+const BENCH_MINUTES: [f64; 5] = [24.0, 20.0, 16.0, 12.0, 8.0];
+// Energy below which a player asks to come out
+const MIN_ENERGY: f32 = 60.0;
+// How far above MIN_ENERGY a bench player has to be before going back in
+const RESTED_MARGIN: f32 = 20.0;
+
+// Minutes each player should get over the game and how tired they may get
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationPlan {
+    // (player id, target minutes)
+    minutes: Vec<(i64, f64)>,
+    min_energy: f32,
+}
+
+impl RotationPlan {
+    pub fn new(starters: &[Player], bench: &[Player]) -> RotationPlan {
+        let starters = starters
+            .iter()
+            .filter_map(|p| p.get_id().ok())
+            .map(|id| (id, STARTER_MINUTES));
+        let bench = bench
+            .iter()
+            .filter_map(|p| p.get_id().ok())
+            .enumerate()
+            .map(|(i, id)| (id, BENCH_MINUTES.get(i).copied().unwrap_or(0.0)));
+        RotationPlan {
+            minutes: starters.chain(bench).collect(),
+            min_energy: MIN_ENERGY,
+        }
+    }
+
+    pub fn target_minutes(&self, player_id: i64) -> f64 {
+        self.minutes
+            .iter()
+            .find(|(id, _)| *id == player_id)
+            .map(|(_, minutes)| *minutes)
+            .unwrap_or_default()
+    }
+
+    pub fn min_energy(&self) -> f32 {
+        self.min_energy
+    }
+}
+
+impl TeamState {
+    fn minutes_played(&self, player_id: i64) -> f64 {
+        self.box_score
+            .iter()
+            .find(|s| s.player_id() == player_id)
+            .map(|s| s.minutes())
+            .unwrap_or_default()
+    }
+
+    // Energy of a player on the bench, players who haven't played are fresh
+    fn resting_energy(&self, player_id: i64) -> f32 {
+        self.resting_energy
+            .get(&player_id)
+            .copied()
+            .unwrap_or(MAX_ENERGY)
+    }
+
+    // Bench index of the rested player with the most planned minutes left,
+    // preferring the position of the player at `index`
+    fn best_replacement(&self, index: usize) -> Option<usize> {
+        let position = self.active_players[index].0.get_position();
+        self.bench
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, player)| {
+                let id = player.get_id().ok()?;
+                let remaining = self.rotation.target_minutes(id) - self.minutes_played(id);
+                let rested = self.resting_energy(id) >= self.rotation.min_energy() + RESTED_MARGIN;
+                if remaining <= 0.0 || !rested {
+                    return None;
+                }
+                let fit = if player.get_position() == position {
+                    10.0
+                } else {
+                    0.0
+                };
+                Some((i, remaining + fit))
+            })
+            .fold(None, |best: Option<(usize, f64)>, candidate| match best {
+                Some(b) if b.1 >= candidate.1 => Some(b),
+                _ => Some(candidate),
+            })
+            .map(|(i, _)| i)
+    }
+}
+
+// Subs `team` makes at a dead ball. Tired players and players past their
+// planned minutes come out if someone on the bench can take their place.
+pub fn check_substitutions(game: &mut Game, team: Possession) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    for index in 0..5 {
+        let team_state = &game.state.team_state[team.index()];
+        let (player, state) = &team_state.active_players[index];
+        let player_id = player.get_id().unwrap_or_default();
+        let tired = state.energy < team_state.rotation.min_energy();
+        let done =
+            team_state.minutes_played(player_id) >= team_state.rotation.target_minutes(player_id);
+        if !tired && !done {
+            continue;
+        }
+        if let Some(bench_index) = team_state.best_replacement(index) {
+            if let Some(event) = game.substitute(team, index, bench_index) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+impl Game {
+    // Swaps the active player at `index` for bench player `bench_index`, who
+    // takes over their spot on the floor
+    pub fn substitute(
+        &mut self,
+        team: Possession,
+        index: usize,
+        bench_index: usize,
+    ) -> Option<GameEvent> {
+        let team_state = &mut self.state.team_state[team.index()];
+        if bench_index >= team_state.bench.0.len() {
+            return None;
+        }
+        let player_in = team_state.bench.0.remove(bench_index);
+        let mut state = team_state.active_players[index].1;
+        state.energy = team_state.resting_energy(player_in.get_id().unwrap_or_default());
+        let (player_out, state_out) =
+            std::mem::replace(&mut team_state.active_players[index], (player_in, state));
+        if let Ok(id) = player_out.get_id() {
+            team_state.resting_energy.insert(id, state_out.energy);
+        }
+        let kind = GameEventKind::Substitution {
+            team,
+            player_in: team_state.active_players[index]
+                .0
+                .get_id()
+                .unwrap_or_default(),
+            player_out: player_out.get_id().unwrap_or_default(),
+        };
+        team_state.bench.0.push(player_out);
+        // New faces on the floor mean new matchups
+        if let Some((offense, _)) = self.state.possession {
+            self.assign_matchups(offense.other());
+        }
+        Some(GameEvent::new(kind, self))
+    }
+
+    // Takes the active player at `index` out for the rest of the game. Returns
+    // None when nobody is left on the bench and the player has to stay on.
+    pub fn disqualify(&mut self, team: Possession, index: usize) -> Option<GameEvent> {
+        let team_state = &self.state.team_state[team.index()];
+        if team_state.bench.0.is_empty() {
+            return None;
+        }
+        let bench_index = team_state.best_replacement(index).unwrap_or(0);
+        let event = self.substitute(team, index, bench_index)?;
+        let team_state = &mut self.state.team_state[team.index()];
+        if let Some(player) = team_state.bench.0.pop() {
+            team_state.bench.1.push(player);
+        }
+        Some(event)
+    }
+
+    // Substitution made by the user while the game is paused
+    pub fn manual_substitution(
+        &mut self,
+        team: Possession,
+        player_out: i64,
+        player_in: i64,
+    ) -> Result<GameEvent, String> {
+        let team_state = &self.state.team_state[team.index()];
+        let index = team_state
+            .active_players
+            .iter()
+            .position(|(p, _)| p.get_id() == Ok(player_out))
+            .ok_or("Player is not on the floor")?;
+        let bench_index = team_state
+            .bench
+            .0
+            .iter()
+            .position(|p| p.get_id() == Ok(player_in))
+            .ok_or("Player is not available on the bench")?;
+        let event = self
+            .substitute(team, index, bench_index)
+            .ok_or("Substitution failed")?;
+        self.events.push(event.clone());
+        Ok(event)
+    }
+}
+
+#[test]
+fn test_bench_plays() {
+    let mut game = crate::game::test_game(9);
+    game.simulate_to_end().unwrap();
+    let (home, _) = game.get_box_score();
+    let game_minutes = home.iter().map(|s| s.minutes()).sum::<f64>() / 5.0;
+    let bench_minutes: f64 = home.iter().skip(5).map(|s| s.minutes()).sum();
+    assert!(bench_minutes > 0.0);
+    // Nobody plays every minute
+    assert!(home.iter().take(5).all(|s| s.minutes() < game_minutes));
+}
@@ -0,0 +1,287 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Three-point distance in feet the shooting ratings are based on
+const NBA_THREE_POINT_LINE: f32 = 23.75;
+// Change in make chance per foot the line is shorter or longer than the NBA's
+const THREE_POINT_FACTOR_PER_FOOT: f32 = 0.03;
+
+// Everything that differs between leagues. Lengths are in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleSet {
+    // Only custom rule sets stored in the database have an id
+    id: Option<i64>,
+    pub name: String,
+    pub periods: u8,
+    pub period_length: u64,
+    pub overtime_length: u64,
+    pub shot_clock: u64,
+    // Shot clock after an offensive rebound or a non-shooting foul
+    pub shot_clock_reset: u64,
+    pub foul_out_limit: u8,
+    // Team fouls in a period from which non-shooting fouls give free throws
+    pub bonus_fouls: u8,
+    // Team fouls from which the bonus is two shots, before that it is a
+    // one-and-one. None when the bonus is always two shots.
+    pub double_bonus_fouls: Option<u8>,
+    // Distance from the basket in feet
+    pub three_point_line: f32,
+    pub timeouts: u8,
+    // Most a team can carry into the last regulation period
+    pub final_period_timeouts: u8,
+    // Most a team can use in the last two minutes of the game
+    pub late_game_timeouts: u8,
+    pub overtime_timeouts: u8,
+    // Held balls and period starts after the opening tip go by the
+    // alternating possession arrow instead of a jump ball
+    #[serde(default)]
+    pub possession_arrow: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::nba()
+    }
+}
+
+impl RuleSet {
+    pub fn nba() -> RuleSet {
+        RuleSet {
+            id: None,
+            name: "NBA".to_string(),
+            periods: 4,
+            period_length: 720,
+            overtime_length: 300,
+            shot_clock: 24,
+            shot_clock_reset: 14,
+            foul_out_limit: 6,
+            bonus_fouls: 5,
+            double_bonus_fouls: None,
+            three_point_line: NBA_THREE_POINT_LINE,
+            timeouts: 7,
+            final_period_timeouts: 4,
+            late_game_timeouts: 2,
+            overtime_timeouts: 2,
+            possession_arrow: false,
+        }
+    }
+
+    pub fn fiba() -> RuleSet {
+        RuleSet {
+            id: None,
+            name: "FIBA".to_string(),
+            periods: 4,
+            period_length: 600,
+            overtime_length: 300,
+            shot_clock: 24,
+            shot_clock_reset: 14,
+            foul_out_limit: 5,
+            bonus_fouls: 5,
+            double_bonus_fouls: None,
+            three_point_line: 22.15,
+            timeouts: 5,
+            final_period_timeouts: 3,
+            late_game_timeouts: 2,
+            overtime_timeouts: 1,
+            possession_arrow: true,
+        }
+    }
+
+    pub fn ncaa() -> RuleSet {
+        RuleSet {
+            id: None,
+            name: "NCAA".to_string(),
+            periods: 2,
+            period_length: 1200,
+            overtime_length: 300,
+            shot_clock: 30,
+            shot_clock_reset: 20,
+            foul_out_limit: 5,
+            bonus_fouls: 7,
+            double_bonus_fouls: Some(10),
+            three_point_line: 22.15,
+            timeouts: 4,
+            final_period_timeouts: 4,
+            late_game_timeouts: 4,
+            overtime_timeouts: 1,
+            possession_arrow: true,
+        }
+    }
+
+    pub fn presets() -> Vec<RuleSet> {
+        vec![RuleSet::nba(), RuleSet::fiba(), RuleSet::ncaa()]
+    }
+
+    pub fn get_id(&self) -> Option<i64> {
+        self.id
+    }
+
+    // Game clock at the start of `period`
+    pub fn period_time(&self, period: u8) -> Duration {
+        if period > self.periods {
+            Duration::from_secs(self.overtime_length)
+        } else {
+            Duration::from_secs(self.period_length)
+        }
+    }
+
+    // Last period before the long break
+    pub fn halftime_period(&self) -> u8 {
+        (self.periods / 2).max(1)
+    }
+
+    // Free throws for a non-shooting foul that makes `team_fouls` in the
+    // period, and whether they are a one-and-one
+    pub fn bonus_free_throws(&self, team_fouls: u8) -> (u8, bool) {
+        if team_fouls < self.bonus_fouls {
+            return (0, false);
+        }
+        match self.double_bonus_fouls {
+            Some(double) if team_fouls < double => (2, true),
+            _ => (2, false),
+        }
+    }
+
+    // Multiplier on three-point shot chances for a line closer or further
+    // than the one the ratings assume
+    pub fn three_point_factor(&self) -> f32 {
+        (1.0 + (NBA_THREE_POINT_LINE - self.three_point_line) * THREE_POINT_FACTOR_PER_FOOT)
+            .max(0.1)
+    }
+
+    // Checks a custom rule set before it is stored or played with
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule set needs a name".to_string());
+        }
+        if self.periods == 0 {
+            return Err("A game needs at least one period".to_string());
+        }
+        if self.period_length == 0 || self.overtime_length == 0 {
+            return Err("Periods need a length".to_string());
+        }
+        if self.shot_clock == 0 || self.shot_clock_reset > self.shot_clock {
+            return Err("The shot clock reset can't be longer than the shot clock".to_string());
+        }
+        if self.foul_out_limit == 0 {
+            return Err("The foul out limit has to be at least one".to_string());
+        }
+        if matches!(self.double_bonus_fouls, Some(double) if double < self.bonus_fouls) {
+            return Err("The double bonus can't start before the bonus".to_string());
+        }
+        if self.three_point_line.is_nan() || self.three_point_line <= 0.0 {
+            return Err("The three-point line needs a distance".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn write_to_db(&mut self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO rule_sets (
+                id,
+                name,
+                periods,
+                period_length,
+                overtime_length,
+                shot_clock,
+                shot_clock_reset,
+                foul_out_limit,
+                bonus_fouls,
+                double_bonus_fouls,
+                three_point_line,
+                timeouts,
+                final_period_timeouts,
+                late_game_timeouts,
+                overtime_timeouts,
+                possession_arrow
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                self.id,
+                self.name,
+                self.periods,
+                self.period_length,
+                self.overtime_length,
+                self.shot_clock,
+                self.shot_clock_reset,
+                self.foul_out_limit,
+                self.bonus_fouls,
+                self.double_bonus_fouls,
+                self.three_point_line,
+                self.timeouts,
+                self.final_period_timeouts,
+                self.late_game_timeouts,
+                self.overtime_timeouts,
+                self.possession_arrow,
+            ],
+        )?;
+        self.id = Some(conn.last_insert_rowid());
+        Ok(())
+    }
+
+    pub fn get_rule_set_from_db(id: i64, db: &Connection) -> Result<RuleSet, rusqlite::Error> {
+        db.query_row(
+            &format!("SELECT {} FROM rule_sets WHERE id = ?", RULE_SET_COLUMNS),
+            [id],
+            RuleSet::from_row,
+        )
+    }
+
+    pub fn get_all_rule_sets_from_db(db: &Connection) -> Result<Vec<RuleSet>, rusqlite::Error> {
+        let mut stmt = db.prepare(&format!(
+            "SELECT {} FROM rule_sets ORDER BY name",
+            RULE_SET_COLUMNS
+        ))?;
+        let rule_sets = stmt
+            .query_map([], RuleSet::from_row)?
+            .collect::<Result<Vec<RuleSet>, _>>()?;
+        Ok(rule_sets)
+    }
+
+    fn from_row(row: &Row) -> Result<RuleSet, rusqlite::Error> {
+        Ok(RuleSet {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            periods: row.get(2)?,
+            period_length: row.get(3)?,
+            overtime_length: row.get(4)?,
+            shot_clock: row.get(5)?,
+            shot_clock_reset: row.get(6)?,
+            foul_out_limit: row.get(7)?,
+            bonus_fouls: row.get(8)?,
+            double_bonus_fouls: row.get(9)?,
+            three_point_line: row.get(10)?,
+            timeouts: row.get(11)?,
+            final_period_timeouts: row.get(12)?,
+            late_game_timeouts: row.get(13)?,
+            overtime_timeouts: row.get(14)?,
+            possession_arrow: row.get(15)?,
+        })
+    }
+}
+
+// Column order read by RuleSet::from_row
+const RULE_SET_COLUMNS: &str = "id, name, periods, period_length, overtime_length, shot_clock,
+    shot_clock_reset, foul_out_limit, bonus_fouls, double_bonus_fouls, three_point_line,
+    timeouts, final_period_timeouts, late_game_timeouts, overtime_timeouts, possession_arrow";
+
+#[test]
+fn test_custom_rule_set_round_trip() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    crate::util::migrations::migrate(&mut conn).unwrap();
+    let mut rules = RuleSet {
+        name: "Streetball".to_string(),
+        periods: 1,
+        period_length: 900,
+        double_bonus_fouls: Some(8),
+        ..RuleSet::fiba()
+    };
+    assert!(rules.validate().is_ok());
+    rules.write_to_db(&conn).unwrap();
+    let stored = RuleSet::get_rule_set_from_db(rules.get_id().unwrap(), &conn).unwrap();
+    assert_eq!(stored, rules);
+    assert_eq!(RuleSet::get_all_rule_sets_from_db(&conn).unwrap().len(), 1);
+
+    rules.shot_clock_reset = rules.shot_clock + 1;
+    assert!(rules.validate().is_err());
+}
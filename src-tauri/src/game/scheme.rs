@@ -0,0 +1,363 @@
+use crate::game::court::{find_path, get_position_weight, CourtArea};
+use crate::game::{Game, Possession};
+use crate::player::player_state::PlayerAction;
+use crate::player::Player;
+use serde::{Deserialize, Serialize};
+
+// Shot chance multipliers, above 1.0 helps the shooter
+// The ball handler's defender got caught on a screen and didn't switch
+const SCREENED_FACTOR: f32 = 1.1;
+// Zones pack the paint and give up open looks from outside
+const ZONE_INSIDE_FACTOR: f32 = 0.9;
+const ZONE_OUTSIDE_FACTOR: f32 = 1.08;
+// The big waiting in the paint in drop coverage protects the rim
+const DROP_INSIDE_FACTOR: f32 = 0.9;
+// Where the screener's defender waits in drop coverage
+const DROP_AREA: CourtArea = CourtArea::FreeThrowLine;
+// Where defenders caught behind the ball run back to
+const TRANSITION_AREA: CourtArea = CourtArea::RestrictedAreaMiddle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum DefensiveScheme {
+    // Everyone guards their matchup and fights over screens
+    #[default]
+    ManToMan,
+    // Man-to-man, but the defenders involved in a screen swap matchups
+    SwitchEverything,
+    // Man-to-man, the screener's defender sags into the paint on ball screens
+    DropCoverage,
+    // Two up top, three along the baseline
+    TwoThreeZone,
+    // Three up top, two on the blocks
+    ThreeTwoZone,
+}
+
+impl DefensiveScheme {
+    pub fn is_zone(&self) -> bool {
+        matches!(
+            self,
+            DefensiveScheme::TwoThreeZone | DefensiveScheme::ThreeTwoZone
+        )
+    }
+
+    // Home spots of the zone, guards first and bigs last
+    fn zone_spots(&self) -> Option<[CourtArea; 5]> {
+        match self {
+            DefensiveScheme::TwoThreeZone => Some([
+                CourtArea::ElbowLeft,
+                CourtArea::ElbowRight,
+                CourtArea::ShortCornerLeft,
+                CourtArea::ShortCornerRight,
+                CourtArea::RestrictedAreaMiddle,
+            ]),
+            DefensiveScheme::ThreeTwoZone => Some([
+                CourtArea::MidrangeWingLeft,
+                CourtArea::MidrangeWingRight,
+                CourtArea::FreeThrowLine,
+                CourtArea::LowPostLeft,
+                CourtArea::LowPostRight,
+            ]),
+            _ => None,
+        }
+    }
+}
+
+// Steps between two areas, how far a zone defender has to go
+fn distance(from: CourtArea, to: CourtArea) -> usize {
+    find_path(from, to).map_or(usize::MAX, |path| path.len())
+}
+
+fn is_inside(area: CourtArea) -> bool {
+    get_position_weight(area) < 2.0
+}
+
+// How badly `defender` fits guarding `offense`: a different position, a size
+// difference, and a weak defender on a dangerous scorer
+fn matchup_cost(defender: &Player, offense: &Player) -> i32 {
+    let position = if defender.get_position() == offense.get_position() {
+        0
+    } else {
+        10
+    };
+    let size = (defender.get_height() - offense.get_height()).abs()
+        + (defender.attributes().strength - offense.attributes().strength).abs() / 5;
+    let o = offense.attributes();
+    let d = defender.attributes();
+    let threat = o.close_shot.max(o.mid_shot).max(o.deep_shot) + o.handle;
+    let stopper = if o.close_shot > o.deep_shot {
+        d.interior_def
+    } else {
+        d.perimeter_def
+    };
+    position + size + threat * (100 - stopper) / 500
+}
+
+// Matchups with the lowest total cost out of every way to pair the fives.
+// matchups[d] is the offensive player defender d guards.
+pub fn best_matchups(defense: &[&Player; 5], offense: &[&Player; 5]) -> [usize; 5] {
+    let mut best = ([0, 1, 2, 3, 4], i32::MAX);
+    let mut current = [0, 1, 2, 3, 4];
+    permutations(&mut current, 0, &mut |matchups| {
+        let cost = (0..5)
+            .map(|d| matchup_cost(defense[d], offense[matchups[d]]))
+            .sum::<i32>();
+        if cost < best.1 {
+            best = (*matchups, cost);
+        }
+    });
+    best.0
+}
+
+fn permutations(items: &mut [usize; 5], k: usize, visit: &mut impl FnMut(&[usize; 5])) {
+    if k == items.len() {
+        visit(items);
+        return;
+    }
+    for i in k..items.len() {
+        items.swap(k, i);
+        permutations(items, k + 1, visit);
+        items.swap(k, i);
+    }
+}
+
+impl Game {
+    pub fn set_defensive_scheme(&mut self, team: Possession, scheme: DefensiveScheme) {
+        self.state.team_state[team.index()].scheme = scheme;
+    }
+
+    // Pairs up the team on defense with the offense, called whenever the ball
+    // or a lineup changes
+    pub fn assign_matchups(&mut self, defense: Possession) {
+        let players = |team: Possession| -> [&Player; 5] {
+            std::array::from_fn(|i| &self.state.team_state[team.index()].active_players[i].0)
+        };
+        let matchups = best_matchups(&players(defense), &players(defense.other()));
+        self.state.team_state[defense.index()].matchups = matchups;
+    }
+
+    // Zone spot of each defender, the biggest players take the back line
+    fn zone_assignments(&self, defense: Possession) -> Option<[CourtArea; 5]> {
+        let spots = self.state.team_state[defense.index()].scheme.zone_spots()?;
+        let players = &self.state.team_state[defense.index()].active_players;
+        let mut by_size: Vec<usize> = (0..5).collect();
+        by_size
+            .sort_by_key(|&i| players[i].0.get_height() * 10 + players[i].0.attributes().strength);
+        let mut assignments = spots;
+        for (spot, &defender) in by_size.iter().enumerate() {
+            assignments[defender] = spots[spot];
+        }
+        Some(assignments)
+    }
+
+    // Active index of the defender responsible for the offensive player at
+    // `index` of `team`: their matchup, or in a zone whoever's spot is closest
+    pub fn defender_of(&self, team: Possession, index: usize) -> usize {
+        let defense = team.other();
+        if let Some(spots) = self.zone_assignments(defense) {
+            let area = self.state.team_state[team.index()].active_players[index]
+                .1
+                .current_area;
+            return (0..5)
+                .min_by_key(|&d| distance(spots[d], area))
+                .unwrap_or(index);
+        }
+        self.state.team_state[defense.index()]
+            .matchups
+            .iter()
+            .position(|&o| o == index)
+            .unwrap_or(index)
+    }
+
+    // Teammate of the ball handler setting a ball screen on them
+    fn ball_screener(&self) -> Option<usize> {
+        let (team, index) = self.state.possession?;
+        let players = &self.state.team_state[team.index()].active_players;
+        let area = players[index].1.current_area;
+        (0..5).find(|&i| {
+            i != index
+                && players[i].1.action == PlayerAction::BallScreen
+                && players[i].1.current_area == area
+        })
+    }
+
+    // Swaps matchups on screens when switching everything. The screen stays
+    // up for several ticks but is only switched once.
+    pub fn switch_on_screens(&mut self) {
+        let Some((team, index)) = self.state.possession else {
+            return;
+        };
+        if self.state.team_state[team.other().index()].scheme != DefensiveScheme::SwitchEverything {
+            return;
+        }
+        let Some(screener) = self.ball_screener() else {
+            return;
+        };
+        let handler_defender = self.defender_of(team, index);
+        let screener_defender = self.defender_of(team, screener);
+        let Some(play) = self.state.play.as_mut() else {
+            return;
+        };
+        if play.switched == Some(screener) {
+            return;
+        }
+        play.switched = Some(screener);
+        let matchups = &mut self.state.team_state[team.other().index()].matchups;
+        matchups.swap(handler_defender, screener_defender);
+    }
+
+    // Area each defender heads for this tick. Man defenders follow their
+    // matchup, zone defenders step out to a player in their part of the zone
+    // and otherwise hold their spot.
+    pub fn defense_targets(&self, defense: Possession) -> [CourtArea; 5] {
+        let offense = &self.state.team_state[defense.other().index()].active_players;
+        if let Some(spots) = self.zone_assignments(defense) {
+            let mut targets = spots;
+            for (i, (_, state)) in offense.iter().enumerate() {
+                let d = self.defender_of(defense.other(), i);
+                if distance(state.current_area, spots[d]) <= 2 {
+                    targets[d] = state.current_area;
+                }
+            }
+            return self.get_back(defense, targets);
+        }
+        let matchups = self.state.team_state[defense.index()].matchups;
+        let mut targets = matchups.map(|o| offense[o].1.current_area);
+        if self.state.team_state[defense.index()].scheme == DefensiveScheme::DropCoverage {
+            if let Some(screener) = self.ball_screener() {
+                targets[self.defender_of(defense.other(), screener)] = DROP_AREA;
+            }
+        }
+        self.get_back(defense, targets)
+    }
+
+    // Defenders the ball got past run back to protect the rim before they
+    // pick anyone up
+    fn get_back(&self, defense: Possession, mut targets: [CourtArea; 5]) -> [CourtArea; 5] {
+        let ball_past = self.state.possession.is_some() && !self.in_transition();
+        let defenders = &self.state.team_state[defense.index()].active_players;
+        for (d, (_, state)) in defenders.iter().enumerate() {
+            if ball_past && !state.current_area.is_front_court() {
+                targets[d] = TRANSITION_AREA;
+            }
+        }
+        targets
+    }
+
+    // Shot chance multiplier from how the defense plays the shooter
+    pub fn scheme_shot_factor(&self, team: Possession, area: CourtArea) -> f32 {
+        let scheme = self.state.team_state[team.other().index()].scheme;
+        let mut factor = 1.0;
+        if scheme.is_zone() {
+            factor *= if is_inside(area) {
+                ZONE_INSIDE_FACTOR
+            } else if area.points() == 3 {
+                ZONE_OUTSIDE_FACTOR
+            } else {
+                1.0
+            };
+        }
+        let screened = self.ball_screener().is_some();
+        match scheme {
+            DefensiveScheme::ManToMan if screened => factor *= SCREENED_FACTOR,
+            DefensiveScheme::DropCoverage => {
+                if is_inside(area) {
+                    factor *= DROP_INSIDE_FACTOR;
+                } else if screened {
+                    factor *= SCREENED_FACTOR;
+                }
+            }
+            _ => {}
+        }
+        factor
+    }
+}
+
+#[test]
+fn test_matchups_by_size() {
+    use crate::player::player_attributes::PlayerAttributes;
+
+    let player = |position: &str, height: i32| {
+        Player::new(
+            Some(height as i64),
+            "First".to_string(),
+            "Last".to_string(),
+            position.to_string(),
+            25,
+            height,
+            200,
+            PlayerAttributes::new(),
+        )
+    };
+    let offense = [
+        player("C", 84),
+        player("PG", 73),
+        player("PF", 81),
+        player("SG", 76),
+        player("SF", 79),
+    ];
+    let defense = [
+        player("PG", 74),
+        player("SG", 77),
+        player("SF", 78),
+        player("PF", 82),
+        player("C", 85),
+    ];
+    let matchups = best_matchups(
+        &std::array::from_fn(|i| &defense[i]),
+        &std::array::from_fn(|i| &offense[i]),
+    );
+    assert_eq!(matchups, [1, 3, 4, 2, 0]);
+}
+
+#[test]
+fn test_zone_guards_areas() {
+    let mut game = crate::game::test_game(2);
+    game.state.possession = Some((Possession::Home, 0));
+    game.set_defensive_scheme(Possession::Away, DefensiveScheme::TwoThreeZone);
+    let spots = game.zone_assignments(Possession::Away).unwrap();
+    for (i, area) in [CourtArea::ShortCornerLeft, CourtArea::ElbowRight]
+        .into_iter()
+        .enumerate()
+    {
+        game.state.team_state[0].active_players[i].1.current_area = area;
+        let defender = game.defender_of(Possession::Home, i);
+        assert_eq!(spots[defender], area);
+    }
+}
+
+#[test]
+fn test_screen_is_switched_once() {
+    use crate::game::play::{ActivePlay, PlayRole, PlayType};
+
+    let mut game = crate::game::test_game(2);
+    game.set_defensive_scheme(Possession::Away, DefensiveScheme::SwitchEverything);
+    game.state.possession = Some((Possession::Home, 0));
+    game.state.play = Some(ActivePlay {
+        play: PlayType::PickAndRoll,
+        roles: [
+            PlayRole::Primary,
+            PlayRole::Screener,
+            PlayRole::Spacer,
+            PlayRole::Spacer,
+            PlayRole::Spacer,
+        ],
+        ticks: 0,
+        switched: None,
+    });
+    let players = &mut game.state.team_state[0].active_players;
+    players[0].1.current_area = CourtArea::ThreePointLineCenter;
+    players[1].1.current_area = CourtArea::ThreePointLineCenter;
+    players[1].1.action = PlayerAction::BallScreen;
+    let before = game.state.team_state[1].matchups;
+
+    // The screen is set for two ticks, the defenders only trade places once
+    game.switch_on_screens();
+    let switched = game.state.team_state[1].matchups;
+    assert_ne!(switched, before);
+    game.switch_on_screens();
+    assert_eq!(game.state.team_state[1].matchups, switched);
+    assert_eq!(
+        game.defender_of(Possession::Home, 0),
+        before.iter().position(|&o| o == 1).unwrap()
+    );
+}
@@ -0,0 +1,143 @@
+use crate::game::court::CourtArea;
+use crate::game::event::game_event::{GameEvent, GameEventKind};
+use crate::game::{Game, Possession};
+use std::time::Duration;
+
+const TIMEOUT_RECOVERY: f32 = 8.0;
+// Unanswered points by the opponent that make the AI stop the game
+const RUN_TIMEOUT: u16 = 8;
+// Average energy of the five on the floor below which the AI wants a breather
+const TIRED_TIMEOUT: f32 = 50.0;
+const LATE_GAME: Duration = Duration::from_secs(120);
+// Where the offense sets up after advancing the ball, by active player index
+const ADVANCE_AREAS: [CourtArea; 5] = [
+    CourtArea::ThreePointLineCenter,
+    CourtArea::ThreePointLineWingLeft,
+    CourtArea::ThreePointLineWingRight,
+    CourtArea::ThreePointLineCornerLeft,
+    CourtArea::ThreePointLineCornerRight,
+];
+
+impl Game {
+    fn is_late_game(&self) -> bool {
+        self.state.period >= self.rules.periods && self.state.time <= LATE_GAME
+    }
+
+    // Timeouts the team can still use right now
+    pub fn available_timeouts(&self, team: Possession) -> u8 {
+        let remaining = match team {
+            Possession::Home => self.state.timeouts.0,
+            Possession::Away => self.state.timeouts.1,
+        };
+        if self.is_late_game() {
+            remaining.min(self.rules.late_game_timeouts)
+        } else {
+            remaining
+        }
+    }
+
+    // Caps or resets the allowance at the start of a period
+    pub fn start_period_timeouts(&mut self) {
+        let cap = self.rules.final_period_timeouts;
+        if self.state.period == self.rules.periods {
+            self.state.timeouts.0 = self.state.timeouts.0.min(cap);
+            self.state.timeouts.1 = self.state.timeouts.1.min(cap);
+        } else if self.state.period > self.rules.periods {
+            let overtime = self.rules.overtime_timeouts;
+            self.state.timeouts = (overtime, overtime);
+        }
+    }
+
+    // Timeout requested by the user, taken at the next dead ball or right away
+    // if the team has the ball
+    pub fn call_timeout(&mut self, team: Possession) -> Result<(), String> {
+        if self.available_timeouts(team) == 0 {
+            return Err(format!("{} has no timeouts left", team));
+        }
+        self.pending_timeout = Some(team);
+        Ok(())
+    }
+
+    // Stops play for `team`. Everyone gets some energy back, and in the last
+    // two minutes a team with the ball advances it to the frontcourt.
+    pub fn take_timeout(&mut self, team: Possession) -> Option<GameEvent> {
+        let available = self.available_timeouts(team);
+        if available == 0 {
+            return None;
+        }
+        match team {
+            Possession::Home => self.state.timeouts.0 = available - 1,
+            Possession::Away => self.state.timeouts.1 = available - 1,
+        }
+        self.recover_energy(TIMEOUT_RECOVERY);
+        if let Some((run_team, _)) = self.state.run {
+            if run_team != team {
+                self.state.run = None;
+            }
+        }
+        let has_ball = matches!(self.state.possession, Some((t, _)) if t == team);
+        if has_ball && self.is_late_game() {
+            let team_state = &mut self.state.team_state[team.index()];
+            for (i, (_, state)) in team_state.active_players.iter_mut().enumerate() {
+                state.current_area = ADVANCE_AREAS[i];
+            }
+        }
+        Some(GameEvent::new(GameEventKind::Timeout { team }, self))
+    }
+}
+
+// Team the AI calls a timeout for at this dead ball, if any: to stop an
+// opponent run, to rest tired players, or to set up a late possession in a
+// close game
+pub fn ai_timeout(game: &Game) -> Option<Possession> {
+    if matches!(game.events.last(), Some(e) if matches!(e.kind, GameEventKind::Timeout { .. })) {
+        return None;
+    }
+    let margin = game.state.score.0 as i32 - game.state.score.1 as i32;
+    [Possession::Home, Possession::Away]
+        .into_iter()
+        .filter(|team| game.available_timeouts(*team) > 0)
+        .find(|team| {
+            let opponent_run = matches!(
+                game.state.run,
+                Some((run_team, points)) if run_team != *team && points >= RUN_TIMEOUT
+            );
+            let energy = game.state.team_state[team.index()]
+                .active_players
+                .iter()
+                .map(|(_, s)| s.energy)
+                .sum::<f32>()
+                / 5.0;
+            let has_ball = matches!(game.state.possession, Some((t, _)) if t == *team);
+            let close_late = game.is_late_game() && margin.abs() <= 3 && has_ball;
+            opponent_run || energy < TIRED_TIMEOUT || close_late
+        })
+}
+
+#[test]
+fn test_timeout_allowances() {
+    let mut game = crate::game::test_game(1);
+    let rules = game.rules.clone();
+    assert_eq!(game.available_timeouts(Possession::Home), rules.timeouts);
+    assert!(game.take_timeout(Possession::Home).is_some());
+    assert_eq!(
+        game.available_timeouts(Possession::Home),
+        rules.timeouts - 1
+    );
+
+    game.state.period = rules.periods;
+    game.start_period_timeouts();
+    assert_eq!(
+        game.available_timeouts(Possession::Home),
+        rules.final_period_timeouts
+    );
+    game.state.time = Duration::from_secs(60);
+    assert_eq!(
+        game.available_timeouts(Possession::Home),
+        rules.late_game_timeouts
+    );
+    game.take_timeout(Possession::Home);
+    game.take_timeout(Possession::Home);
+    assert!(game.take_timeout(Possession::Home).is_none());
+    assert!(game.call_timeout(Possession::Home).is_err());
+}
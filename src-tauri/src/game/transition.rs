@@ -0,0 +1,117 @@
+use crate::game::court::CourtArea;
+use crate::game::{Game, Possession};
+use crate::player::player_state::PlayerAction;
+
+// Lanes of the break: the ball up the middle, two wings running to the
+// corners, a big to the rim and a trailer
+const BALL_LANE: CourtArea = CourtArea::ThreePointLineCenter;
+const WING_LANES: [CourtArea; 2] = [
+    CourtArea::ThreePointLineCornerLeft,
+    CourtArea::ThreePointLineCornerRight,
+];
+const RIM_LANE: CourtArea = CourtArea::RestrictedAreaMiddle;
+const TRAILER_LANE: CourtArea = CourtArea::ThreePointLineWingLeft;
+
+impl Game {
+    // The ball handler hasn't brought the ball past half court yet
+    pub fn in_transition(&self) -> bool {
+        match self.state.possession {
+            Some((team, index)) => !self.state.team_state[team.index()].active_players[index]
+                .1
+                .current_area
+                .is_front_court(),
+            None => false,
+        }
+    }
+
+    // More attackers than defenders past half court
+    pub fn is_fast_break(&self) -> bool {
+        let Some((team, _)) = self.state.possession else {
+            return false;
+        };
+        let ahead = |team: Possession| {
+            self.state.team_state[team.index()]
+                .active_players
+                .iter()
+                .filter(|(_, state)| state.current_area.is_front_court())
+                .count()
+        };
+        let attackers = ahead(team);
+        attackers > 0 && attackers > ahead(team.other())
+    }
+
+    // Where everyone on `team` runs while the ball comes up the floor. The
+    // biggest player runs to the rim, the next biggest trails and the others
+    // fill the wings.
+    pub fn transition_assignments(
+        &self,
+        team: Possession,
+        ball_handler: usize,
+    ) -> [(CourtArea, PlayerAction); 5] {
+        let players = &self.state.team_state[team.index()].active_players;
+        let mut others: Vec<usize> = (0..5).filter(|&i| i != ball_handler).collect();
+        others.sort_by_key(|&i| {
+            -(players[i].0.get_height() * 10 + players[i].0.attributes().strength)
+        });
+        let mut lanes = [(BALL_LANE, PlayerAction::Idle); 5];
+        lanes[others[0]] = (RIM_LANE, PlayerAction::Cut);
+        lanes[others[1]] = (TRAILER_LANE, PlayerAction::SpotUp);
+        lanes[others[2]] = (WING_LANES[0], PlayerAction::SpotUp);
+        lanes[others[3]] = (WING_LANES[1], PlayerAction::SpotUp);
+        lanes
+    }
+}
+
+#[test]
+fn test_transition_after_change_of_possession() {
+    let mut game = crate::game::test_game(6);
+    game.state.possession = Some((Possession::Home, 0));
+    for team_state in game.state.team_state.iter_mut() {
+        for (_, state) in team_state.active_players.iter_mut() {
+            state.current_area = CourtArea::RestrictedAreaMiddle;
+        }
+    }
+    // Everyone under the basket ends up under the far one for the other team
+    game.change_possession(Some((Possession::Away, 0)));
+    assert!(game.in_transition());
+    for team_state in game.state.team_state.iter() {
+        for (_, state) in team_state.active_players.iter() {
+            assert_eq!(state.current_area, CourtArea::BackcourtRestrictedAreaMiddle);
+        }
+    }
+
+    // It takes several ticks to bring the ball up
+    let mut ticks = 0;
+    while game.in_transition() {
+        game.update_player_states().unwrap();
+        ticks += 1;
+        assert!(ticks < 10, "The ball never crossed half court");
+    }
+    assert!(ticks >= 2);
+}
+
+#[test]
+fn test_backcourt_violation_on_the_dribble() {
+    use crate::game::event::game_event::{GameEventKind, TurnoverReason};
+
+    let mut game = crate::game::test_game(7);
+    game.state.possession = Some((Possession::Home, 0));
+    // Still bringing the ball up
+    game.state.team_state[0].active_players[0].1.current_area = CourtArea::Backcourt;
+    assert!(!game.backcourt_violation());
+    game.state.team_state[0].active_players[0].1.current_area = CourtArea::ThreePointLineCenter;
+    assert!(!game.backcourt_violation());
+
+    // Dribbling back over half court gives the ball away
+    game.state.team_state[0].active_players[0].1.current_area = CourtArea::Center;
+    assert!(game.backcourt_violation());
+    assert!(matches!(
+        game.queued_events.back().map(|e| &e.kind),
+        Some(GameEventKind::Turnover {
+            reason: TurnoverReason::Backcourt,
+            ..
+        })
+    ));
+    assert!(matches!(game.state.possession, Some((Possession::Away, _))));
+    assert!(!game.backcourt_violation());
+}
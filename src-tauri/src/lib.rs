@@ -37,22 +37,203 @@ fn get_team_starting_lineup(app_handle: AppHandle, team_id: i64) -> [player::Pla
 }
 
 #[tauri::command]
-fn load_game(app_handle: AppHandle, state: tauri::State<AppState>) -> game::Game {
-    let new_game = app_handle.db(|db| game::Game::new(&db).unwrap());
+fn load_game(
+    app_handle: AppHandle,
+    state: tauri::State<AppState>,
+    seed: Option<u64>,
+    rules: Option<game::rules::RuleSet>,
+) -> Result<game::Game, String> {
+    let rules = rules.unwrap_or_default();
+    rules.validate()?;
+    let new_game = app_handle
+        .db(|db| game::Game::new(&db, seed, rules))
+        .map_err(|e| e.to_string())?;
     *state.game.lock().unwrap() = Some(new_game.clone());
-    return new_game;
+    Ok(new_game)
+}
+
+// Presets first, then the custom rule sets in the database
+#[tauri::command]
+fn get_rule_sets(app_handle: AppHandle) -> Result<Vec<game::rules::RuleSet>, String> {
+    let custom = app_handle
+        .db(|db| game::rules::RuleSet::get_all_rule_sets_from_db(db))
+        .map_err(|e| e.to_string())?;
+    Ok(game::rules::RuleSet::presets()
+        .into_iter()
+        .chain(custom)
+        .collect())
+}
+
+#[tauri::command]
+fn save_rule_set(
+    app_handle: AppHandle,
+    mut rules: game::rules::RuleSet,
+) -> Result<game::rules::RuleSet, String> {
+    rules.validate()?;
+    app_handle
+        .db(|db| rules.write_to_db(db))
+        .map_err(|e| e.to_string())?;
+    Ok(rules)
+}
+
+#[tauri::command]
+fn get_box_score(
+    state: tauri::State<AppState>,
+) -> Result<
+    (
+        Vec<player::player_stats::PlayerStats>,
+        Vec<player::player_stats::PlayerStats>,
+    ),
+    String,
+> {
+    let game = state.game.lock().unwrap();
+    Ok(game.as_ref().ok_or("Game not initialized")?.get_box_score())
+}
+
+#[tauri::command]
+fn get_games(app_handle: AppHandle) -> Result<Vec<game::history::GameSummary>, String> {
+    app_handle
+        .db(|db| game::history::GameSummary::get_games_from_db(db))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_game_box_score(
+    app_handle: AppHandle,
+    game_id: i64,
+) -> Result<
+    (
+        Vec<player::player_stats::PlayerStats>,
+        Vec<player::player_stats::PlayerStats>,
+    ),
+    String,
+> {
+    app_handle
+        .db(|db| game::history::GameSummary::get_game(game_id, db)?.get_box_score(db))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_game_events(
+    app_handle: AppHandle,
+    game_id: i64,
+) -> Result<Vec<game::event::game_event::GameEvent>, String> {
+    app_handle
+        .db(|db| game::event::game_event::GameEvent::get_game_events_from_db(game_id, db))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_injuries(app_handle: AppHandle) -> Result<Vec<player::injury::Injury>, String> {
+    app_handle
+        .db(|db| player::injury::Injury::get_active_injuries_from_db(db))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn predict_matchup(
+    app_handle: AppHandle,
+    home_team_id: i64,
+    away_team_id: i64,
+    simulations: u32,
+    seed: Option<u64>,
+    rules: Option<game::rules::RuleSet>,
+    save_games: Option<bool>,
+) -> Result<game::predictor::MatchupPrediction, String> {
+    let rules = rules.unwrap_or_default();
+    rules.validate()?;
+    let matchup = app_handle
+        .db(|db| game::predictor::Matchup::load(db, home_team_id, away_team_id, rules))
+        .map_err(|e| e.to_string())?;
+    let save_games = save_games.unwrap_or(false);
+    // Thousands of games would block the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        game::predictor::MatchupPrediction::simulate(&matchup, simulations, seed, |game| {
+            if save_games {
+                save_game(&app_handle, game)?;
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn substitute_player(
+    app_handle: AppHandle,
+    state: tauri::State<AppState>,
+    team: game::Possession,
+    player_out: i64,
+    player_in: i64,
+) -> Result<game::event::game_event::GameEvent, String> {
+    if state.running.load(std::sync::atomic::Ordering::SeqCst) {
+        return Err("Pause the game before making a substitution".to_string());
+    }
+    let mut game = state.game.lock().unwrap();
+    let current_game = game.as_mut().ok_or("Game not initialized")?;
+    let event = current_game.manual_substitution(team, player_out, player_in)?;
+    app_handle
+        .emit_to("main", "game_event", event.clone())
+        .map_err(|e| e.to_string())?;
+    app_handle
+        .emit_to("main", "player_states", current_game.get_player_states())
+        .map_err(|e| e.to_string())?;
+    Ok(event)
+}
+
+#[tauri::command]
+fn call_timeout(state: tauri::State<AppState>, team: game::Possession) -> Result<(), String> {
+    let mut game = state.game.lock().unwrap();
+    game.as_mut()
+        .ok_or("Game not initialized")?
+        .call_timeout(team)
+}
+
+#[tauri::command]
+fn set_defensive_scheme(
+    state: tauri::State<AppState>,
+    team: game::Possession,
+    scheme: game::scheme::DefensiveScheme,
+) -> Result<(), String> {
+    let mut game = state.game.lock().unwrap();
+    game.as_mut()
+        .ok_or("Game not initialized")?
+        .set_defensive_scheme(team, scheme);
+    Ok(())
+}
+
+// Stores a finished game in the history
+fn save_game(app_handle: &AppHandle, game: &mut game::Game) -> Result<i64, String> {
+    app_handle.db(|db| game.save_finished(db))
 }
 
 fn simulate_game(app_handle: AppHandle, speed: u8) -> Result<(), Box<dyn std::error::Error>> {
     app_handle.emit("main", "simulation_started")?;
+    // Saved after the game lock is released, the simulation ends either way
+    let saved = match play_game(&app_handle, speed) {
+        Ok(Some(mut finished)) => save_game(&app_handle, &mut finished).map(|_| ()),
+        Ok(None) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+    app_handle.emit("main", "simulation_ended")?;
+    saved?;
+    Ok(())
+}
+
+// Plays events until the simulation is stopped, returns the game once it has
+// finished
+fn play_game(
+    app_handle: &AppHandle,
+    speed: u8,
+) -> Result<Option<game::Game>, Box<dyn std::error::Error>> {
     let running = app_handle.state::<AppState>().running.clone();
     let state = app_handle.state::<AppState>();
     while running.load(std::sync::atomic::Ordering::SeqCst) {
         let mut game = state.game.lock().unwrap();
-        let event = game
-            .as_mut()
-            .ok_or("Game not initialized")?
-            .generate_next_game_event()?;
+        let current_game = game.as_mut().ok_or("Game not initialized")?;
+        current_game.print_state();
+        let event = current_game.generate_next_game_event()?;
 
         match speed {
             1 => {
@@ -71,6 +252,8 @@ fn simulate_game(app_handle: AppHandle, speed: u8) -> Result<(), Box<dyn std::er
 
         println!("{:?}", event);
         app_handle.emit_to("main", "game_event", event.clone())?;
+        let possession = game.as_ref().unwrap().get_possession();
+        app_handle.emit_to("main", "possession", possession)?;
         app_handle.emit_to(
             "main",
             "player_states",
@@ -78,13 +261,14 @@ fn simulate_game(app_handle: AppHandle, speed: u8) -> Result<(), Box<dyn std::er
         )?;
         let game_score = game.as_ref().unwrap().get_score();
         app_handle.emit_to("main", "game_score", game_score)?;
+        let box_score = game.as_ref().unwrap().get_box_score();
+        app_handle.emit_to("main", "box_score", box_score)?;
 
         if event.is_game_end() {
-            break;
+            return Ok(game.as_ref().cloned());
         }
     }
-    app_handle.emit("main", "simulation_ended")?;
-    Ok(())
+    Ok(None)
 }
 
 #[tauri::command]
@@ -159,6 +343,17 @@ pub fn run() {
             get_team_starting_lineup,
             get_team,
             load_game,
+            get_rule_sets,
+            save_rule_set,
+            get_box_score,
+            get_games,
+            get_game_box_score,
+            get_game_events,
+            get_injuries,
+            predict_matchup,
+            substitute_player,
+            call_timeout,
+            set_defensive_scheme,
             start_sim,
             stop_sim,
             set_sim_speed
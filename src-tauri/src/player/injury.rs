@@ -0,0 +1,129 @@
+use rand::Rng;
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Condition for queries joining players that leaves out anyone still hurt
+pub const NOT_INJURED: &str =
+    "players.id NOT IN (SELECT player_id FROM player_injuries WHERE games_remaining > 0)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InjuryType {
+    Bruise,
+    Sprain,
+    Strain,
+    Concussion,
+    Fracture,
+    Tear,
+}
+
+impl fmt::Display for InjuryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl InjuryType {
+    fn parse(s: &str) -> Option<InjuryType> {
+        match s {
+            "Bruise" => Some(InjuryType::Bruise),
+            "Sprain" => Some(InjuryType::Sprain),
+            "Strain" => Some(InjuryType::Strain),
+            "Concussion" => Some(InjuryType::Concussion),
+            "Fracture" => Some(InjuryType::Fracture),
+            "Tear" => Some(InjuryType::Tear),
+            _ => None,
+        }
+    }
+
+    // Games missed, inclusive range
+    fn games_out(&self) -> (u32, u32) {
+        match self {
+            InjuryType::Bruise => (0, 1),
+            InjuryType::Sprain => (1, 5),
+            InjuryType::Strain => (2, 8),
+            InjuryType::Concussion => (1, 4),
+            InjuryType::Fracture => (10, 30),
+            InjuryType::Tear => (30, 80),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Injury {
+    pub player_id: i64,
+    pub injury: InjuryType,
+    // Games the player misses, counted down as their team plays
+    pub games_remaining: u32,
+}
+
+impl Injury {
+    // Random injury, minor ones are by far the most common
+    pub fn generate(player_id: i64, rng: &mut impl Rng) -> Injury {
+        let roll = rng.gen_range(0..100);
+        let injury = match roll {
+            0..=39 => InjuryType::Bruise,
+            40..=64 => InjuryType::Sprain,
+            65..=84 => InjuryType::Strain,
+            85..=91 => InjuryType::Concussion,
+            92..=97 => InjuryType::Fracture,
+            _ => InjuryType::Tear,
+        };
+        let (min, max) = injury.games_out();
+        Injury {
+            player_id,
+            injury,
+            games_remaining: rng.gen_range(min..=max),
+        }
+    }
+
+    pub fn write_to_db(&self, game_id: Option<i64>, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO player_injuries (player_id, game_id, injury, games_remaining) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                self.player_id,
+                game_id,
+                self.injury.to_string(),
+                self.games_remaining,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_active_injuries_from_db(db: &Connection) -> Result<Vec<Injury>, rusqlite::Error> {
+        let mut stmt = db.prepare(
+            "SELECT player_id, injury, games_remaining FROM player_injuries WHERE games_remaining > 0 ORDER BY id",
+        )?;
+        let injuries = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<Injury>, _>>()?;
+        Ok(injuries)
+    }
+
+    // One game closer to returning for everyone on either team
+    pub fn heal_after_game(
+        home_team_id: Option<i64>,
+        away_team_id: Option<i64>,
+        conn: &Connection,
+    ) -> rusqlite::Result<()> {
+        conn.execute(
+            "UPDATE player_injuries SET games_remaining = games_remaining - 1
+            WHERE games_remaining > 0 AND player_id IN (
+                SELECT player_id FROM team_players WHERE team_id IN (?1, ?2)
+                UNION SELECT player_id FROM team_starting_lineup WHERE team_id IN (?1, ?2)
+                UNION SELECT player_id FROM team_bench WHERE team_id IN (?1, ?2)
+            )",
+            params![home_team_id, away_team_id],
+        )?;
+        Ok(())
+    }
+
+    fn from_row(row: &Row) -> Result<Injury, rusqlite::Error> {
+        let injury: String = row.get(1)?;
+        Ok(Injury {
+            player_id: row.get(0)?,
+            injury: InjuryType::parse(&injury).unwrap_or(InjuryType::Bruise),
+            games_remaining: row.get(2)?,
+        })
+    }
+}
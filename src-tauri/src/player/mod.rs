@@ -1,8 +1,9 @@
+pub mod injury;
 pub mod player_attributes;
 pub mod player_state;
 pub mod player_stats;
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -49,6 +50,10 @@ impl Player {
         }
     }
 
+    pub fn get_position(&self) -> &str {
+        &self.position
+    }
+
     pub fn get_height(&self) -> i32 {
         self.height
     }
@@ -67,30 +72,44 @@ impl Player {
         )?;
         let id = conn.last_insert_rowid();
         self.id = Some(id);
+        self.attributes.write_to_db(id, conn)?;
         Ok(())
     }
 
     pub fn get_all_players_from_db(db: &Connection) -> Result<Vec<Player>, rusqlite::Error> {
-        let mut stmt = db.prepare(
-            "SELECT id, first_name, last_name, position, age, height, weight FROM players",
-        )?;
+        let mut stmt = db.prepare(&format!(
+            "SELECT {}
+            FROM players
+            INNER JOIN player_attributes ON player_attributes.player_id = players.id",
+            Player::select_columns()
+        ))?;
         let players: Vec<Player> = stmt
-            .query_map([], |row| {
-                Ok(Player {
-                    id: row.get(0)?,
-                    first_name: row.get(1)?,
-                    last_name: row.get(2)?,
-                    position: row.get(3)?,
-                    age: row.get(4)?,
-                    height: row.get(5)?,
-                    weight: row.get(6)?,
-                    attributes: player_attributes::gen_rand_attrs(),
-                })
-            })?
+            .query_map([], Player::from_row)?
             .collect::<Result<Vec<Player>, _>>()?;
         Ok(players)
     }
 
+    // Columns read by Player::from_row, the query has to join player_attributes
+    pub fn select_columns() -> String {
+        format!(
+            "players.id, players.first_name, players.last_name, players.position, players.age, players.height, players.weight, {}",
+            player_attributes::ATTRIBUTE_COLUMNS
+        )
+    }
+
+    pub fn from_row(row: &Row) -> Result<Player, rusqlite::Error> {
+        Ok(Player {
+            id: row.get(0)?,
+            first_name: row.get(1)?,
+            last_name: row.get(2)?,
+            position: row.get(3)?,
+            age: row.get(4)?,
+            height: row.get(5)?,
+            weight: row.get(6)?,
+            attributes: player_attributes::PlayerAttributes::from_row(row, 7)?,
+        })
+    }
+
     pub fn attributes(&self) -> &player_attributes::PlayerAttributes {
         &self.attributes
     }
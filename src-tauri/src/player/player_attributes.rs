@@ -1,11 +1,11 @@
 use crate::game::court::CourtArea;
 use rand::Rng;
-use rusqlite::Connection;
+use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
 use super::Player;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlayerAttributes {
     pub spd: i32,
     pub interior_def: i32,
@@ -25,6 +25,7 @@ pub struct PlayerAttributes {
     pub strength: i32,
     pub durability: i32,
     pub conditioning: i32,
+    pub free_throw: i32,
 }
 
 impl PlayerAttributes {
@@ -48,6 +49,7 @@ impl PlayerAttributes {
             strength: 0,
             durability: 0,
             conditioning: 0,
+            free_throw: 0,
         }
     }
     pub fn shot_chance(&self, area: CourtArea) -> f32 {
@@ -82,23 +84,61 @@ impl PlayerAttributes {
             CourtArea::Center => {
                 return 0.1;
             }
-            CourtArea::Backcourt
-            | CourtArea::SidelineLeft
-            | CourtArea::SidelineRight
-            | CourtArea::BaselineLeft
-            | CourtArea::BaselineRight
-            | CourtArea::OutOfBounds => {
+            // The other half and out of bounds
+            _ => {
                 return 0.0;
             }
         }
     }
 
+    // Chance from 0.45 to 0.9 of making a single free throw
+    pub fn free_throw_chance(&self) -> f32 {
+        0.45 + 0.45 * self.free_throw as f32 / 100.0
+    }
+
     pub fn get_player_attributes(
         player: &Player,
         db: &Connection,
     ) -> Result<PlayerAttributes, rusqlite::Error> {
-        let mut stmt = db.prepare(
-            "SELECT
+        let mut stmt = db.prepare(&format!(
+            "SELECT {} FROM player_attributes WHERE player_id = ?",
+            ATTRIBUTE_COLUMNS
+        ))?;
+        let player_attributes =
+            stmt.query_row([player.get_id().unwrap()], |row| Self::from_row(row, 0))?;
+
+        Ok(player_attributes)
+    }
+
+    // Reads the ATTRIBUTE_COLUMNS starting at column `start`
+    pub fn from_row(row: &Row, start: usize) -> Result<PlayerAttributes, rusqlite::Error> {
+        Ok(PlayerAttributes {
+            spd: row.get(start)?,
+            interior_def: row.get(start + 1)?,
+            perimeter_def: row.get(start + 2)?,
+            close_shot: row.get(start + 3)?,
+            mid_shot: row.get(start + 4)?,
+            deep_shot: row.get(start + 5)?,
+            shot_in_traffic: row.get(start + 6)?,
+            intelligence: row.get(start + 7)?,
+            handle: row.get(start + 8)?,
+            pass: row.get(start + 9)?,
+            block: row.get(start + 10)?,
+            steal: row.get(start + 11)?,
+            off_rebound: row.get(start + 12)?,
+            def_rebound: row.get(start + 13)?,
+            ath: row.get(start + 14)?,
+            strength: row.get(start + 15)?,
+            durability: row.get(start + 16)?,
+            conditioning: row.get(start + 17)?,
+            free_throw: row.get(start + 18)?,
+        })
+    }
+
+    pub fn write_to_db(&self, player_id: i64, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO player_attributes (
+                player_id,
                 spd,
                 interior_def,
                 perimeter_def,
@@ -116,38 +156,58 @@ impl PlayerAttributes {
                 athleticism,
                 strength,
                 durability,
-                conditioning
-            FROM
-                player_attributes
-            WHERE
-                player_id = ?",
+                conditioning,
+                free_throw
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+            params![
+                player_id,
+                self.spd,
+                self.interior_def,
+                self.perimeter_def,
+                self.close_shot,
+                self.mid_shot,
+                self.deep_shot,
+                self.shot_in_traffic,
+                self.intelligence,
+                self.handle,
+                self.pass,
+                self.block,
+                self.steal,
+                self.off_rebound,
+                self.def_rebound,
+                self.ath,
+                self.strength,
+                self.durability,
+                self.conditioning,
+                self.free_throw,
+            ],
         )?;
-        let player_attributes = stmt.query_row([player.get_id().unwrap()], |row| {
-            Ok(PlayerAttributes {
-                spd: row.get(0)?,
-                interior_def: row.get(1)?,
-                perimeter_def: row.get(2)?,
-                close_shot: row.get(3)?,
-                mid_shot: row.get(4)?,
-                deep_shot: row.get(5)?,
-                shot_in_traffic: row.get(6)?,
-                intelligence: row.get(7)?,
-                handle: row.get(8)?,
-                pass: row.get(9)?,
-                block: row.get(10)?,
-                steal: row.get(11)?,
-                off_rebound: row.get(12)?,
-                def_rebound: row.get(13)?,
-                ath: row.get(14)?,
-                strength: row.get(15)?,
-                durability: row.get(16)?,
-                conditioning: row.get(17)?,
-            })
-        })?;
-
-        Ok(player_attributes)
+        Ok(())
     }
 }
+
+// Column order read by PlayerAttributes::from_row
+pub const ATTRIBUTE_COLUMNS: &str = "
+    player_attributes.spd,
+    player_attributes.interior_def,
+    player_attributes.perimeter_def,
+    player_attributes.close_shot,
+    player_attributes.mid_shot,
+    player_attributes.deep_shot,
+    player_attributes.shot_in_traffic,
+    player_attributes.intelligence,
+    player_attributes.handle,
+    player_attributes.pass,
+    player_attributes.block,
+    player_attributes.steal,
+    player_attributes.off_rebound,
+    player_attributes.def_rebound,
+    player_attributes.athleticism,
+    player_attributes.strength,
+    player_attributes.durability,
+    player_attributes.conditioning,
+    player_attributes.free_throw";
+
 pub fn gen_rand_attrs() -> PlayerAttributes {
     let mut rand = rand::thread_rng();
 
@@ -170,5 +230,6 @@ pub fn gen_rand_attrs() -> PlayerAttributes {
         strength: rand.gen_range(0..100),
         durability: rand.gen_range(0..100),
         conditioning: rand.gen_range(0..100),
+        free_throw: rand.gen_range(0..100),
     }
 }
@@ -1,5 +1,6 @@
 use crate::game::court::{self, go_towards, is_between_basket, CourtArea};
 use crate::player::player_attributes;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -31,10 +32,19 @@ impl fmt::Display for PlayerAction {
     }
 }
 
+pub const MAX_ENERGY: f32 = 100.0;
+
+fn max_energy() -> f32 {
+    MAX_ENERGY
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PlayerState {
     pub action: PlayerAction,
     pub current_area: court::CourtArea,
+    // 0 to MAX_ENERGY, drained by playing and recovered on the bench and in breaks
+    #[serde(default = "max_energy")]
+    pub energy: f32,
 }
 
 impl PlayerState {
@@ -43,58 +53,90 @@ impl PlayerState {
             Some(value) => PlayerState {
                 action: PlayerAction::Idle,
                 current_area: value,
+                energy: MAX_ENERGY,
             },
             None => PlayerState {
                 action: PlayerAction::Idle,
                 current_area: court::CourtArea::Center,
+                energy: MAX_ENERGY,
             },
         }
     }
-    pub fn generate_offensive_player_action(
-        &mut self,
-        attributes: &player_attributes::PlayerAttributes,
-        has_ball: bool,
-    ) {
-        let actions;
+
+    // Multiplier from 0.75 when exhausted to 1.0 when fresh, for shooting,
+    // moving and defending
+    pub fn energy_factor(&self) -> f32 {
+        0.75 + 0.25 * self.energy / MAX_ENERGY
+    }
+
+    // Energy used per second of the current action. Well conditioned players
+    // tire slower.
+    pub fn drain_energy(&mut self, attributes: &player_attributes::PlayerAttributes, seconds: f32) {
+        let rate = match self.action {
+            PlayerAction::Drive | PlayerAction::Layup | PlayerAction::Dunk => 0.12,
+            PlayerAction::Cut => 0.1,
+            PlayerAction::Rebound
+            | PlayerAction::Contest
+            | PlayerAction::Block
+            | PlayerAction::DefendTight
+            | PlayerAction::Steal => 0.09,
+            PlayerAction::BallScreen | PlayerAction::OffBallScreen | PlayerAction::Foul => 0.07,
+            PlayerAction::Pass | PlayerAction::Shoot | PlayerAction::Defend => 0.06,
+            PlayerAction::ShootOfDribble | PlayerAction::SpotUp | PlayerAction::DefendLoose => 0.05,
+            PlayerAction::Idle => 0.02,
+        };
+        let conditioning = 1.4 - 0.8 * attributes.conditioning as f32 / 100.0;
+        self.energy = (self.energy - rate * conditioning * seconds).max(0.0);
+    }
+
+    pub fn recover_energy(&mut self, amount: f32) {
+        self.energy = (self.energy + amount).min(MAX_ENERGY);
+    }
+    // Off the ball players pick something to do. The ball handler's choice is
+    // made by the game once everyone has moved, see Game::decide.
+    pub fn generate_offensive_player_action(&mut self, has_ball: bool, rng: &mut impl Rng) {
         if has_ball {
-            let shot_chance = self.calculate_shot_chance(attributes);
-            if shot_chance < 0.3 {
-                actions = vec![PlayerAction::Pass, PlayerAction::Drive]
+            return;
+        }
+        let actions = [
+            PlayerAction::Rebound,
+            PlayerAction::Cut,
+            PlayerAction::BallScreen,
+            PlayerAction::OffBallScreen,
+            PlayerAction::SpotUp,
+        ];
+        let index = rng.gen_range(0..actions.len());
+        self.action = actions[index];
+    }
+
+    // How the ball handler gets the shot off from here
+    pub fn shot_action(
+        &self,
+        attributes: &player_attributes::PlayerAttributes,
+        rng: &mut impl Rng,
+    ) -> PlayerAction {
+        let inside_shot_areas = [
+            CourtArea::RestrictedAreaLeft,
+            CourtArea::RestrictedAreaRight,
+            CourtArea::RestrictedAreaMiddle,
+            CourtArea::LowPostLeft,
+            CourtArea::LowPostRight,
+            CourtArea::ShortCornerLeft,
+            CourtArea::ShortCornerRight,
+        ];
+        let driving = self.action == PlayerAction::Drive;
+        if inside_shot_areas.contains(&self.current_area) && driving {
+            // Only the athletic ones throw it down
+            if rng.gen_range(0..100) < attributes.ath / 2 {
+                PlayerAction::Dunk
             } else {
-                let inside_shot_areas = [
-                    CourtArea::RestrictedAreaLeft,
-                    CourtArea::RestrictedAreaRight,
-                    CourtArea::RestrictedAreaMiddle,
-                    CourtArea::LowPostLeft,
-                    CourtArea::LowPostRight,
-                    CourtArea::ShortCornerLeft,
-                    CourtArea::ShortCornerRight,
-                ];
-                if inside_shot_areas.contains(&self.current_area) {
-                    if self.action == PlayerAction::Drive {
-                        actions = vec![PlayerAction::Layup, PlayerAction::Dunk];
-                    } else {
-                        actions = vec![PlayerAction::Shoot];
-                    }
-                } else {
-                    if self.action == PlayerAction::Drive {
-                        actions = vec![PlayerAction::ShootOfDribble];
-                    } else {
-                        actions = vec![PlayerAction::Shoot];
-                    }
-                }
+                PlayerAction::Layup
             }
+        } else if driving {
+            PlayerAction::ShootOfDribble
         } else {
-            actions = vec![
-                PlayerAction::Rebound,
-                PlayerAction::Cut,
-                PlayerAction::BallScreen,
-                PlayerAction::OffBallScreen,
-                PlayerAction::SpotUp,
-            ];
+            PlayerAction::Shoot
         }
-        let index = rand::random::<usize>() % actions.len();
-        self.action = actions[index];
     }
     //Function that returns Some(2, 3) or None
     pub fn is_shot(&self) -> Option<u8> {
@@ -110,16 +152,17 @@ impl PlayerState {
             _ => return None,
         }
     }
-    pub fn generate_defensive_player_action(&mut self) {
+    pub fn generate_defensive_player_action(&mut self, rng: &mut impl Rng) {
         let actions = vec![
             PlayerAction::Block,
+            PlayerAction::Contest,
             PlayerAction::DefendTight,
             PlayerAction::Defend,
             PlayerAction::DefendLoose,
             PlayerAction::Steal,
             PlayerAction::Foul,
         ];
-        let index = rand::random::<usize>() % actions.len();
+        let index = rng.gen_range(0..actions.len());
         self.action = actions[index]
     }
     pub fn generate_defense_player_next_area(&mut self, opp_area: CourtArea) {
@@ -130,43 +173,146 @@ impl PlayerState {
             self.current_area = go_towards(self.current_area, goal_area)
         }
     }
-    pub fn generate_offensive_player_next_area(&mut self) {
-        let available_areas = court::can_move_to(self.current_area);
-        let index = rand::random::<usize>() % available_areas.len();
-        self.current_area = available_areas.into_iter().nth(index).unwrap()
+    // Moves towards `area` if the legs allow it and picks a defensive action
+    pub fn defend(&mut self, area: CourtArea, rng: &mut impl Rng) {
+        if rng.gen_range(0.0..1.0) < self.energy_factor() {
+            self.generate_defense_player_next_area(area);
+        }
+        self.generate_defensive_player_action(rng);
     }
 
+    pub fn generate_offensive_player_next_area(&mut self, rng: &mut impl Rng) {
+        let available_areas = court::inbounds_moves(self.current_area);
+        let index = rng.gen_range(0..available_areas.len());
+        self.current_area = available_areas[index]
+    }
+
+    // Follows the team's play: off the ball the player does what the play asks,
+    // with the ball they still decide for themselves. Either way they head for
+    // the play's target area.
+    pub fn run_play(
+        &mut self,
+        has_ball: bool,
+        (target, action): (CourtArea, PlayerAction),
+        rng: &mut impl Rng,
+    ) {
+        if !has_ball {
+            self.action = action;
+        }
+        if rng.gen_range(0.0..1.0) < self.energy_factor() {
+            self.current_area = go_towards(self.current_area, target);
+        }
+    }
+
+    // On the open floor quick players cover an extra area towards `target`
+    pub fn sprint(
+        &mut self,
+        attributes: &player_attributes::PlayerAttributes,
+        target: CourtArea,
+        rng: &mut impl Rng,
+    ) {
+        if !self.current_area.is_front_court()
+            && rng.gen_range(0.0..100.0) < attributes.spd as f32 * self.energy_factor()
+        {
+            self.current_area = go_towards(self.current_area, target);
+        }
+    }
+
+    // The area's make chance scaled from half for a 0 rated shooter to one
+    // and a half for a 100 rated one
     pub fn calculate_shot_chance(&self, attributes: &player_attributes::PlayerAttributes) -> f32 {
         let area_shot_chance = self.current_area.shot_chance();
         let attributes_shot_chance = attributes.shot_chance(self.current_area);
-        let shot_chance = (area_shot_chance * attributes_shot_chance) / 100.0;
-        return shot_chance;
+        let shot_chance = area_shot_chance * (0.5 + attributes_shot_chance / 100.0);
+        shot_chance * self.energy_factor()
     }
 
     pub fn generate_next_player_state(
         &mut self,
-        attributes: &player_attributes::PlayerAttributes,
         is_offense: (bool, bool),
         is_defense: (bool, Option<&PlayerState>),
+        rng: &mut impl Rng,
     ) -> Result<(), String> {
         if is_offense.0 == is_defense.0 {
             return Err("Both offense and defense are the same".to_string());
         }
+        // Tired legs don't always get there
+        let moves = rng.gen_range(0.0..1.0) < self.energy_factor();
         if is_offense.0 {
-            self.generate_offensive_player_action(attributes, is_offense.1);
-            self.generate_offensive_player_next_area();
+            self.generate_offensive_player_action(is_offense.1, rng);
+            if moves {
+                self.generate_offensive_player_next_area(rng);
+            }
             return Ok(());
         } else if is_defense.0 {
             match is_defense.1 {
-                Some(state) => {
-                    self.generate_defense_player_next_area(state.current_area);
+                Some(state) => self.defend(state.current_area, rng),
+                None => {
+                    self.generate_defensive_player_action(rng);
+                    self.generate_defensive_player_action(rng);
                 }
-                None => self.generate_defensive_player_action(),
             }
-            self.generate_defensive_player_action();
             return Ok(());
         }
 
         Err("Neither offense nor defense".to_string())
     }
 }
+
+#[test]
+fn test_drives_drain_more_than_spot_ups() {
+    let attributes = player_attributes::PlayerAttributes::new();
+    let mut driver = PlayerState::new(None);
+    driver.action = PlayerAction::Drive;
+    let mut shooter = PlayerState::new(None);
+    shooter.action = PlayerAction::SpotUp;
+    driver.drain_energy(&attributes, 60.0);
+    shooter.drain_energy(&attributes, 60.0);
+    assert!(driver.energy < shooter.energy);
+    assert!(shooter.energy < MAX_ENERGY);
+
+    driver.recover_energy(MAX_ENERGY);
+    assert_eq!(driver.energy, MAX_ENERGY);
+}
+
+#[test]
+fn test_shot_chance_by_area() {
+    let mut average = player_attributes::PlayerAttributes::new();
+    average.close_shot = 50;
+    average.mid_shot = 50;
+    average.deep_shot = 50;
+    let chance = |area, attributes: &player_attributes::PlayerAttributes| {
+        PlayerState::new(Some(area)).calculate_shot_chance(attributes)
+    };
+    // A fresh average shooter makes about what the league does from each area
+    let expected = [
+        (CourtArea::RestrictedAreaMiddle, 0.6),
+        (CourtArea::LowPostLeft, 0.5),
+        (CourtArea::ShortCornerRight, 0.45),
+        (CourtArea::FreeThrowLine, 0.42),
+        (CourtArea::MidrangeWingLeft, 0.4),
+        (CourtArea::ThreePointLineCornerLeft, 0.38),
+        (CourtArea::ThreePointLineWingRight, 0.35),
+        (CourtArea::ThreePointLineCenter, 0.35),
+    ];
+    for (area, percentage) in expected {
+        assert!(
+            (chance(area, &average) - percentage).abs() < 1e-6,
+            "{:?}",
+            area
+        );
+    }
+    // Layups are worth more than long twos, threes sit in between
+    let points = |area: CourtArea| chance(area, &average) * area.points() as f32;
+    assert!(points(CourtArea::RestrictedAreaMiddle) > points(CourtArea::ThreePointLineWingLeft));
+    assert!(points(CourtArea::ThreePointLineWingLeft) > points(CourtArea::MidrangeWingLeft));
+
+    // Ratings move the chance from half to one and a half times the average
+    let mut best = average.clone();
+    best.close_shot = 100;
+    let mut worst = average.clone();
+    worst.close_shot = 0;
+    let rim = CourtArea::RestrictedAreaMiddle;
+    assert!((chance(rim, &best) - 0.9).abs() < 1e-6);
+    assert!((chance(rim, &worst) - 0.3).abs() < 1e-6);
+}
@@ -1,5 +1,9 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerStats {
-    game_id: i64,
+    game_id: Option<i64>,
     player_id: i64,
     team_id: i64,
     minutes: f64,
@@ -23,9 +27,18 @@ pub struct PlayerStats {
     points: f64,
 }
 
+// Made over attempted, 0 when nothing was attempted
+fn percentage(made: f64, attempted: f64) -> f64 {
+    if attempted > 0.0 {
+        made / attempted
+    } else {
+        0.0
+    }
+}
+
 impl PlayerStats {
     pub fn new(
-        game_id: i64,
+        game_id: Option<i64>,
         player_id: i64,
         team_id: i64,
         minutes: f64,
@@ -45,10 +58,12 @@ impl PlayerStats {
         personal_fouls: f64,
         points: f64,
     ) -> PlayerStats {
-        let field_goal_percentage = field_goals_made / field_goals_attempted;
-        let three_point_field_goal_percentage =
-            three_point_field_goals_made / three_point_field_goals_attempted;
-        let free_throw_percentage = free_throws_made / free_throws_attempted;
+        let field_goal_percentage = percentage(field_goals_made, field_goals_attempted);
+        let three_point_field_goal_percentage = percentage(
+            three_point_field_goals_made,
+            three_point_field_goals_attempted,
+        );
+        let free_throw_percentage = percentage(free_throws_made, free_throws_attempted);
         PlayerStats {
             game_id,
             player_id,
@@ -74,4 +89,208 @@ impl PlayerStats {
             points,
         }
     }
+
+    pub fn empty(game_id: Option<i64>, player_id: i64, team_id: i64) -> PlayerStats {
+        PlayerStats::new(
+            game_id, player_id, team_id, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 0.0,
+        )
+    }
+
+    pub fn player_id(&self) -> i64 {
+        self.player_id
+    }
+
+    pub fn team_id(&self) -> i64 {
+        self.team_id
+    }
+
+    pub fn points(&self) -> f64 {
+        self.points
+    }
+
+    pub fn minutes(&self) -> f64 {
+        self.minutes
+    }
+
+    pub fn steals(&self) -> f64 {
+        self.steals
+    }
+
+    pub fn blocks(&self) -> f64 {
+        self.blocks
+    }
+
+    pub fn personal_fouls(&self) -> u8 {
+        self.personal_fouls as u8
+    }
+
+    pub fn add_seconds(&mut self, seconds: f64) {
+        self.minutes += seconds / 60.0;
+    }
+
+    pub fn record_field_goal(&mut self, points: u8, made: bool) {
+        self.field_goals_attempted += 1.0;
+        if points == 3 {
+            self.three_point_field_goals_attempted += 1.0;
+        }
+        if made {
+            self.field_goals_made += 1.0;
+            if points == 3 {
+                self.three_point_field_goals_made += 1.0;
+            }
+            self.points += points as f64;
+        }
+        self.field_goal_percentage = percentage(self.field_goals_made, self.field_goals_attempted);
+        self.three_point_field_goal_percentage = percentage(
+            self.three_point_field_goals_made,
+            self.three_point_field_goals_attempted,
+        );
+    }
+
+    pub fn record_free_throw(&mut self, made: bool) {
+        self.free_throws_attempted += 1.0;
+        if made {
+            self.free_throws_made += 1.0;
+            self.points += 1.0;
+        }
+        self.free_throw_percentage = percentage(self.free_throws_made, self.free_throws_attempted);
+    }
+
+    pub fn record_rebound(&mut self, offensive: bool) {
+        if offensive {
+            self.offensive_rebounds += 1.0;
+        } else {
+            self.defensive_rebounds += 1.0;
+        }
+        self.total_rebounds += 1.0;
+    }
+
+    pub fn record_assist(&mut self) {
+        self.assists += 1.0;
+    }
+
+    pub fn record_steal(&mut self) {
+        self.steals += 1.0;
+    }
+
+    pub fn record_block(&mut self) {
+        self.blocks += 1.0;
+    }
+
+    pub fn record_turnover(&mut self) {
+        self.turnovers += 1.0;
+    }
+
+    pub fn record_foul(&mut self) {
+        self.personal_fouls += 1.0;
+    }
+
+    pub fn write_to_db(&mut self, game_id: i64, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO player_game_stats (
+                game_id,
+                player_id,
+                team_id,
+                minutes,
+                field_goals_made,
+                field_goals_attempted,
+                three_point_field_goals_made,
+                three_point_field_goals_attempted,
+                free_throws_made,
+                free_throws_attempted,
+                offensive_rebounds,
+                defensive_rebounds,
+                total_rebounds,
+                assists,
+                steals,
+                blocks,
+                turnovers,
+                personal_fouls,
+                points
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+            params![
+                game_id,
+                self.player_id,
+                self.team_id,
+                self.minutes,
+                self.field_goals_made,
+                self.field_goals_attempted,
+                self.three_point_field_goals_made,
+                self.three_point_field_goals_attempted,
+                self.free_throws_made,
+                self.free_throws_attempted,
+                self.offensive_rebounds,
+                self.defensive_rebounds,
+                self.total_rebounds,
+                self.assists,
+                self.steals,
+                self.blocks,
+                self.turnovers,
+                self.personal_fouls,
+                self.points,
+            ],
+        )?;
+        self.game_id = Some(game_id);
+        Ok(())
+    }
+
+    pub fn get_game_stats_from_db(
+        game_id: i64,
+        db: &Connection,
+    ) -> Result<Vec<PlayerStats>, rusqlite::Error> {
+        let mut stmt = db.prepare(
+            "SELECT
+                game_id,
+                player_id,
+                team_id,
+                minutes,
+                field_goals_made,
+                field_goals_attempted,
+                three_point_field_goals_made,
+                three_point_field_goals_attempted,
+                free_throws_made,
+                free_throws_attempted,
+                offensive_rebounds,
+                defensive_rebounds,
+                total_rebounds,
+                assists,
+                steals,
+                blocks,
+                turnovers,
+                personal_fouls,
+                points
+            FROM player_game_stats
+            WHERE game_id = ?
+            ORDER BY id",
+        )?;
+        let stats = stmt
+            .query_map([game_id], Self::from_row)?
+            .collect::<Result<Vec<PlayerStats>, _>>()?;
+        Ok(stats)
+    }
+
+    fn from_row(row: &Row) -> Result<PlayerStats, rusqlite::Error> {
+        Ok(PlayerStats::new(
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            row.get(5)?,
+            row.get(6)?,
+            row.get(7)?,
+            row.get(8)?,
+            row.get(9)?,
+            row.get(10)?,
+            row.get(11)?,
+            row.get(12)?,
+            row.get(13)?,
+            row.get(14)?,
+            row.get(15)?,
+            row.get(16)?,
+            row.get(17)?,
+            row.get(18)?,
+        ))
+    }
 }
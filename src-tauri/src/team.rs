@@ -1,4 +1,6 @@
-use crate::player::{self, player_attributes::gen_rand_attrs};
+use crate::game::play::{self, PlayTendencies};
+use crate::player;
+use crate::player::injury;
 use rusqlite::Connection;
 use std::fmt;
 
@@ -7,11 +9,30 @@ pub struct Team {
     id: Option<i64>,
     name: String,
     city: String,
+    #[serde(default)]
+    tendencies: PlayTendencies,
 }
 
 impl Team {
     pub fn new(id: Option<i64>, name: String, city: String) -> Team {
-        Team { id, name, city }
+        Team {
+            id,
+            name,
+            city,
+            tendencies: PlayTendencies::default(),
+        }
+    }
+
+    pub fn get_id(&self) -> Option<i64> {
+        self.id
+    }
+
+    pub fn get_tendencies(&self) -> &PlayTendencies {
+        &self.tendencies
+    }
+
+    pub fn set_tendencies(&mut self, tendencies: PlayTendencies) {
+        self.tendencies = tendencies;
     }
 
     pub fn write_to_db(&mut self, db: &Connection) -> Result<(), rusqlite::Error> {
@@ -20,35 +41,44 @@ impl Team {
 
         let id = db.last_insert_rowid();
         self.id = Some(id);
+        self.tendencies.write_to_db(id, db)?;
         Ok(())
     }
 
     pub fn get_teams_from_db(db: &Connection) -> Result<Vec<Team>, rusqlite::Error> {
-        let mut stmt = db.prepare("SELECT id, name, city FROM teams")?;
+        let mut stmt = db.prepare(&format!(
+            "SELECT teams.id, teams.name, teams.city, {}
+            FROM teams
+            LEFT JOIN team_tendencies ON team_tendencies.team_id = teams.id",
+            play::TENDENCY_COLUMNS
+        ))?;
         let teams: Vec<Team> = stmt
-            .query_map([], |row| {
-                Ok(Team {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    city: row.get(2)?,
-                })
-            })?
+            .query_map([], Team::from_row)?
             .collect::<Result<Vec<Team>, _>>()?;
         Ok(teams)
     }
 
     pub fn get_team(team_id: &i64, db: &Connection) -> Result<Team, rusqlite::Error> {
-        let mut stmt = db.prepare("SELECT id, name, city FROM teams WHERE id = ?")?;
-        let team = stmt.query_row([team_id], |row| {
-            Ok(Team {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                city: row.get(2)?,
-            })
-        })?;
+        let mut stmt = db.prepare(&format!(
+            "SELECT teams.id, teams.name, teams.city, {}
+            FROM teams
+            LEFT JOIN team_tendencies ON team_tendencies.team_id = teams.id
+            WHERE teams.id = ?",
+            play::TENDENCY_COLUMNS
+        ))?;
+        let team = stmt.query_row([team_id], Team::from_row)?;
         Ok(team)
     }
 
+    fn from_row(row: &rusqlite::Row) -> Result<Team, rusqlite::Error> {
+        Ok(Team {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            city: row.get(2)?,
+            tendencies: PlayTendencies::from_row(row, 3)?,
+        })
+    }
+
     pub fn add_player_to_team(
         &self,
         player: &player::Player,
@@ -70,55 +100,54 @@ impl Team {
         Ok(())
     }
 
+    fn get_healthy_players(
+        &self,
+        table: &str,
+        db: &Connection,
+    ) -> Result<Vec<player::Player>, rusqlite::Error> {
+        let mut stmt = db.prepare(&format!(
+            "SELECT {}
+            FROM {table}
+            INNER JOIN players ON {table}.player_id = players.id
+            INNER JOIN player_attributes ON player_attributes.player_id = players.id
+            WHERE team_id = ? AND {}",
+            player::Player::select_columns(),
+            injury::NOT_INJURED,
+        ))?;
+        let players: Vec<player::Player> = stmt
+            .query_map([self.id], player::Player::from_row)?
+            .collect::<Result<Vec<player::Player>, _>>()?;
+        Ok(players)
+    }
+
+    // Injured starters are replaced by the first healthy bench players
     pub fn get_starting_lineup(
         &self,
         db: &Connection,
     ) -> Result<[player::Player; 5], rusqlite::Error> {
-        let mut stmt = db.prepare("
-            SELECT players.id, players.first_name, players.last_name, players.position, players.age, players.height, players.weight
-            FROM team_starting_lineup
-            INNER JOIN players ON team_starting_lineup.player_id = players.id
-            WHERE team_id = ?")?;
-        let players: [player::Player; 5] = stmt
-            .query_map([self.id], |row| {
-                Ok(player::Player::new(
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                    gen_rand_attrs(),
-                ))
-            })?
-            .collect::<Result<Vec<player::Player>, _>>()?
+        let mut players = self.get_healthy_players("team_starting_lineup", db)?;
+        let missing = 5usize.saturating_sub(players.len());
+        players.extend(
+            self.get_healthy_players("team_bench", db)?
+                .into_iter()
+                .take(missing),
+        );
+        let players: [player::Player; 5] = players
             .try_into()
             //TODO: Add error handling
             .map_err(|_| rusqlite::Error::QueryReturnedNoRows)?;
         Ok(players)
     }
 
+    // Healthy bench players who aren't filling in for an injured starter
     pub fn get_bench(&self, db: &Connection) -> Result<Vec<player::Player>, rusqlite::Error> {
-        let mut stmt = db.prepare("
-            SELECT players.id, players.first_name, players.last_name, players.position, players.age, players.height, players.weight
-            FROM team_bench
-            INNER JOIN players ON team_bench.player_id = players.id
-            WHERE team_id = ?")?;
-        let players: Vec<player::Player> = stmt
-            .query_map([self.id], |row| {
-                Ok(player::Player::new(
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                    gen_rand_attrs(),
-                ))
-            })?
-            .collect::<Result<Vec<player::Player>, _>>()?;
+        let starters = self.get_healthy_players("team_starting_lineup", db)?;
+        let missing = 5usize.saturating_sub(starters.len());
+        let players = self
+            .get_healthy_players("team_bench", db)?
+            .into_iter()
+            .skip(missing)
+            .collect();
         Ok(players)
     }
 }
@@ -129,3 +158,59 @@ impl fmt::Display for Team {
         Ok(())
     }
 }
+
+#[test]
+fn test_injured_starter_left_out() {
+    use crate::player::injury::{Injury, InjuryType};
+    use crate::player::player_attributes::PlayerAttributes;
+
+    let mut db = Connection::open_in_memory().unwrap();
+    crate::util::migrations::migrate(&mut db).unwrap();
+    let mut team = Team::new(None, "Team".to_string(), "City".to_string());
+    team.write_to_db(&db).unwrap();
+    let mut players: Vec<player::Player> = (0..7)
+        .map(|i| {
+            player::Player::new(
+                None,
+                format!("Player{}", i),
+                "Test".to_string(),
+                "SF".to_string(),
+                25,
+                6,
+                200,
+                PlayerAttributes::new(),
+            )
+        })
+        .collect();
+    for (i, player) in players.iter_mut().enumerate() {
+        player.write_to_db(&db).unwrap();
+        team.add_player_to_team(player, &db).unwrap();
+        if i < 5 {
+            team.add_player_to_starting_lineup(player, &db).unwrap();
+        } else {
+            db.execute(
+                "INSERT INTO team_bench (team_id, player_id) VALUES (?, ?)",
+                [team.get_id().unwrap(), player.get_id().unwrap()],
+            )
+            .unwrap();
+        }
+    }
+    let injured = players[0].get_id().unwrap();
+    Injury {
+        player_id: injured,
+        injury: InjuryType::Sprain,
+        games_remaining: 1,
+    }
+    .write_to_db(None, &db)
+    .unwrap();
+
+    let lineup = team.get_starting_lineup(&db).unwrap();
+    assert!(lineup.iter().all(|p| p.get_id() != Ok(injured)));
+    assert!(lineup.iter().any(|p| p.get_id() == players[5].get_id()));
+    assert_eq!(team.get_bench(&db).unwrap().len(), 1);
+
+    // Back after missing a game
+    Injury::heal_after_game(team.get_id(), None, &db).unwrap();
+    let lineup = team.get_starting_lineup(&db).unwrap();
+    assert!(lineup.iter().any(|p| p.get_id() == Ok(injured)));
+}
@@ -1,24 +1,16 @@
 use crate::player;
 use crate::player::player_attributes::gen_rand_attrs;
 use crate::team;
+use crate::util::migrations::{self, MigrationError};
 use rusqlite::{Connection, Result};
 use std::{fs, path::PathBuf};
 
 //Singleton implementation for db
-pub fn init(db_path: &PathBuf) -> Result<Connection, rusqlite::Error> {
+pub fn init(db_path: &PathBuf) -> Result<Connection, MigrationError> {
     fs::create_dir_all(db_path).expect("The app data directory should be created.");
     let sqlite_path = db_path.join("HoopDreams.sqlite");
-    let conn = Connection::open(sqlite_path)?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS teams (
-            id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL UNIQUE,
-            city TEXT NOT NULL UNIQUE
-        );
-        ",
-        [],
-    )?;
+    let mut conn = Connection::open(sqlite_path)?;
+    migrations::migrate(&mut conn)?;
 
     let teams = team::Team::get_teams_from_db(&conn)?;
     if teams.is_empty() {
@@ -31,45 +23,6 @@ pub fn init(db_path: &PathBuf) -> Result<Connection, rusqlite::Error> {
                 .expect("Database write should succeed");
         }
     }
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS players (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            first_name TEXT NOT NULL,
-            last_name TEXT NOT NULL,
-            position TEXT NOT NULL,
-            age INTEGER NOT NULL,
-            height INTEGER NOT NULL,
-            weight INTEGER NOT NULL
-        );
-        ",
-        [],
-    )?;
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS team_players (
-            id INTEGER PRIMARY KEY,
-            team_id INTEGER NOT NULL,
-            player_id INTEGER NOT NULL UNIQUE
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS team_bench (
-            id INTEGER PRIMARY KEY,
-            team_id INTEGER NOT NULL,
-            player_id INTEGER NOT NULL
-        )",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS team_starting_lineup (
-            id INTEGER PRIMARY KEY,
-            team_id INTEGER NOT NULL,
-            player_id INTEGER NOT NULL
-        )",
-        [],
-    )?;
     let players = player::Player::get_all_players_from_db(&conn)?;
     if players.is_empty() {
         let players = vec![
@@ -0,0 +1,336 @@
+use crate::player::player_attributes::gen_rand_attrs;
+use rusqlite::{Connection, Transaction};
+use std::fmt;
+
+type Migration = fn(&Transaction) -> Result<(), rusqlite::Error>;
+
+// MIGRATIONS[n] upgrades a database from version n to n + 1. Never edit a
+// migration that has shipped, append a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    initial_schema,
+    game_history,
+    typed_game_events,
+    free_throw_rating,
+    player_injuries,
+    rule_sets,
+    possession_arrow,
+    team_tendencies,
+    rate_unrated_players,
+];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    // The file was written by a newer version of the app
+    DatabaseTooNew { version: u32, supported: u32 },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "Database error: {}", e),
+            MigrationError::DatabaseTooNew { version, supported } => write!(
+                f,
+                "Database schema version {} is newer than the supported version {}",
+                version, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+pub fn schema_version(conn: &Connection) -> Result<u32, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+// Applies every pending migration in a single transaction
+pub fn migrate(conn: &mut Connection) -> Result<(), MigrationError> {
+    let version = schema_version(conn)?;
+    if version > latest_version() {
+        return Err(MigrationError::DatabaseTooNew {
+            version,
+            supported: latest_version(),
+        });
+    }
+
+    let tx = conn.transaction()?;
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&tx)?;
+    }
+    tx.pragma_update(None, "user_version", latest_version())?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Version 1: the tables that existed before versioning. Save files from that
+// time are at version 0 but already have most of them, hence IF NOT EXISTS.
+fn initial_schema(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS teams (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            city TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE IF NOT EXISTS players (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            first_name TEXT NOT NULL,
+            last_name TEXT NOT NULL,
+            position TEXT NOT NULL,
+            age INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            weight INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS player_attributes (
+            player_id INTEGER PRIMARY KEY REFERENCES players(id),
+            spd INTEGER NOT NULL,
+            interior_def INTEGER NOT NULL,
+            perimeter_def INTEGER NOT NULL,
+            close_shot INTEGER NOT NULL,
+            mid_shot INTEGER NOT NULL,
+            deep_shot INTEGER NOT NULL,
+            shot_in_traffic INTEGER NOT NULL,
+            intelligence INTEGER NOT NULL,
+            handle INTEGER NOT NULL,
+            pass INTEGER NOT NULL,
+            block INTEGER NOT NULL,
+            steal INTEGER NOT NULL,
+            off_rebound INTEGER NOT NULL,
+            def_rebound INTEGER NOT NULL,
+            athleticism INTEGER NOT NULL,
+            strength INTEGER NOT NULL,
+            durability INTEGER NOT NULL,
+            conditioning INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS team_players (
+            id INTEGER PRIMARY KEY,
+            team_id INTEGER NOT NULL,
+            player_id INTEGER NOT NULL UNIQUE
+        );
+        CREATE TABLE IF NOT EXISTS team_bench (
+            id INTEGER PRIMARY KEY,
+            team_id INTEGER NOT NULL,
+            player_id INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS team_starting_lineup (
+            id INTEGER PRIMARY KEY,
+            team_id INTEGER NOT NULL,
+            player_id INTEGER NOT NULL
+        );",
+    )
+}
+
+// Version 2: completed games with their play-by-play and box scores
+fn game_history(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE games (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            home_team_id INTEGER NOT NULL REFERENCES teams(id),
+            away_team_id INTEGER NOT NULL REFERENCES teams(id),
+            home_score INTEGER NOT NULL,
+            away_score INTEGER NOT NULL,
+            periods INTEGER NOT NULL,
+            seed INTEGER NOT NULL,
+            played_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE game_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id INTEGER NOT NULL REFERENCES games(id),
+            sequence INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            time TEXT NOT NULL,
+            period INTEGER NOT NULL,
+            possession TEXT
+        );
+        CREATE INDEX game_events_game_id ON game_events (game_id, sequence);
+        CREATE TABLE player_game_stats (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            game_id INTEGER NOT NULL REFERENCES games(id),
+            player_id INTEGER NOT NULL REFERENCES players(id),
+            team_id INTEGER NOT NULL REFERENCES teams(id),
+            minutes REAL NOT NULL,
+            field_goals_made REAL NOT NULL,
+            field_goals_attempted REAL NOT NULL,
+            three_point_field_goals_made REAL NOT NULL,
+            three_point_field_goals_attempted REAL NOT NULL,
+            free_throws_made REAL NOT NULL,
+            free_throws_attempted REAL NOT NULL,
+            offensive_rebounds REAL NOT NULL,
+            defensive_rebounds REAL NOT NULL,
+            total_rebounds REAL NOT NULL,
+            assists REAL NOT NULL,
+            steals REAL NOT NULL,
+            blocks REAL NOT NULL,
+            turnovers REAL NOT NULL,
+            personal_fouls REAL NOT NULL,
+            points REAL NOT NULL,
+            UNIQUE (game_id, player_id)
+        );",
+    )
+}
+
+// Version 3: GameEventKind stored as JSON next to the narration
+fn typed_game_events(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch("ALTER TABLE game_events ADD COLUMN kind TEXT;")
+}
+
+// Version 4: free throw rating, existing players start from their mid-range shot
+fn free_throw_rating(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "ALTER TABLE player_attributes ADD COLUMN free_throw INTEGER NOT NULL DEFAULT 0;
+        UPDATE player_attributes SET free_throw = mid_shot;",
+    )
+}
+
+// Version 5: injuries and how many games the player still misses
+fn player_injuries(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE player_injuries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            player_id INTEGER NOT NULL REFERENCES players(id),
+            game_id INTEGER REFERENCES games(id),
+            injury TEXT NOT NULL,
+            games_remaining INTEGER NOT NULL,
+            injured_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX player_injuries_player_id ON player_injuries (player_id);",
+    )
+}
+
+// Version 6: custom rule sets, the presets live in code
+fn rule_sets(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE rule_sets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            periods INTEGER NOT NULL,
+            period_length INTEGER NOT NULL,
+            overtime_length INTEGER NOT NULL,
+            shot_clock INTEGER NOT NULL,
+            shot_clock_reset INTEGER NOT NULL,
+            foul_out_limit INTEGER NOT NULL,
+            bonus_fouls INTEGER NOT NULL,
+            double_bonus_fouls INTEGER,
+            three_point_line REAL NOT NULL,
+            timeouts INTEGER NOT NULL,
+            final_period_timeouts INTEGER NOT NULL,
+            late_game_timeouts INTEGER NOT NULL,
+            overtime_timeouts INTEGER NOT NULL
+        );",
+    )
+}
+
+// Version 7: whether a rule set uses the alternating possession arrow
+fn possession_arrow(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "ALTER TABLE rule_sets ADD COLUMN possession_arrow INTEGER NOT NULL DEFAULT 0;",
+    )
+}
+
+// Version 8: how often each team runs its plays, teams without a row use
+// the defaults
+fn team_tendencies(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    tx.execute_batch(
+        "CREATE TABLE team_tendencies (
+            team_id INTEGER PRIMARY KEY REFERENCES teams(id),
+            pick_and_roll INTEGER NOT NULL,
+            isolation INTEGER NOT NULL,
+            post_up INTEGER NOT NULL,
+            horns INTEGER NOT NULL,
+            motion INTEGER NOT NULL
+        );",
+    )
+}
+
+// Version 9: players saved before attributes were stored get rated once here,
+// after every attribute column exists
+fn rate_unrated_players(tx: &Transaction) -> Result<(), rusqlite::Error> {
+    let mut stmt = tx.prepare(
+        "SELECT id FROM players WHERE id NOT IN (SELECT player_id FROM player_attributes)",
+    )?;
+    let unrated_players = stmt
+        .query_map([], |row| row.get::<_, i64>(0))?
+        .collect::<Result<Vec<i64>, _>>()?;
+    for player_id in unrated_players {
+        gen_rand_attrs().write_to_db(player_id, tx)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_migrate() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+    assert_eq!(schema_version(&conn).unwrap(), latest_version());
+    // Running again on an up to date database is a no-op
+    migrate(&mut conn).unwrap();
+    assert_eq!(schema_version(&conn).unwrap(), latest_version());
+
+    conn.pragma_update(None, "user_version", latest_version() + 1)
+        .unwrap();
+    assert!(matches!(
+        migrate(&mut conn),
+        Err(MigrationError::DatabaseTooNew { .. })
+    ));
+}
+
+#[test]
+fn test_player_attributes_round_trip() {
+    use crate::player::player_attributes::PlayerAttributes;
+    use crate::player::Player;
+
+    // A save from before attributes were stored gets its player rated
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE players (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            first_name TEXT NOT NULL,
+            last_name TEXT NOT NULL,
+            position TEXT NOT NULL,
+            age INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            weight INTEGER NOT NULL
+        );
+        INSERT INTO players (first_name, last_name, position, age, height, weight)
+        VALUES ('Old', 'Save', 'PG', 30, 6, 180);",
+    )
+    .unwrap();
+    migrate(&mut conn).unwrap();
+    let players = Player::get_all_players_from_db(&conn).unwrap();
+    assert_eq!(players.len(), 1);
+
+    let attributes = PlayerAttributes {
+        spd: 77,
+        block: 64,
+        conditioning: 12,
+        free_throw: 81,
+        ..PlayerAttributes::new()
+    };
+    let mut player = Player::new(
+        None,
+        "New".to_string(),
+        "Player".to_string(),
+        "C".to_string(),
+        22,
+        7,
+        250,
+        attributes.clone(),
+    );
+    player.write_to_db(&conn).unwrap();
+    let loaded = Player::get_all_players_from_db(&conn)
+        .unwrap()
+        .into_iter()
+        .find(|p| p.get_id() == player.get_id())
+        .unwrap();
+    assert_eq!(loaded.attributes(), &attributes);
+}
@@ -1,12 +1,30 @@
 use rand::distributions::{Distribution, WeightedIndex};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-pub fn rng(home_value: i32, away_value: i32) -> u8 {
+// Every game owns one of these so the same seed replays the same game
+pub type GameRng = StdRng;
+
+// Seeds go to the frontend as JSON numbers, which only hold 53 bits exactly
+const SEED_LIMIT: u64 = 1 << 53;
+
+pub fn new_seed() -> u64 {
+    rand::thread_rng().gen_range(0..SEED_LIMIT)
+}
+
+pub fn seeded(seed: u64) -> GameRng {
+    StdRng::seed_from_u64(seed)
+}
+
+pub fn from_entropy() -> GameRng {
+    StdRng::from_entropy()
+}
+
+pub fn rng<R: Rng + ?Sized>(home_value: i32, away_value: i32, rng: &mut R) -> u8 {
     // 0 = home, 1 = away
     let sub = home_value - away_value;
     let abs_sub = sub.abs();
     let ratio = abs_sub + 50;
-    let mut rng = rand::thread_rng();
     let winner = rng.gen_range(0..100);
 
     if sub == 0 {
@@ -29,11 +47,9 @@ pub fn rng(home_value: i32, away_value: i32) -> u8 {
         }
     }
 }
-pub fn rng_arr(arr: Vec<i32>) -> u8 {
-    let mut rng = rand::thread_rng();
+pub fn rng_arr<R: Rng + ?Sized>(arr: Vec<i32>, rng: &mut R) -> u8 {
     let weights: Vec<_> = arr.iter().map(|&v| v.max(0) as u64).collect();
     let dist = WeightedIndex::new(weights).unwrap();
-    let winner = dist.sample(&mut rng);
-    println!("Winner: {}", winner);
+    let winner = dist.sample(rng);
     winner as u8
 }